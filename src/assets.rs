@@ -0,0 +1,69 @@
+use crate::error::{Error, Result};
+use crate::mesh::{Mesh, load_tris};
+use crate::obj::load_obj;
+
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// directories a relative asset path is looked up in, in order
+pub struct AssetPaths {
+    pub directories: Vec<PathBuf>,
+}
+
+impl AssetPaths {
+    pub fn new(directories: Vec<PathBuf>) -> Self {
+        Self { directories }
+    }
+
+    // the working directory first, then next to the executable, each with an optional assets folder
+    pub fn standard() -> Self {
+        let mut directories = Vec::new();
+        if let Ok(working_directory) = env::current_dir() {
+            directories.push(working_directory.clone());
+            directories.push(working_directory.join("assets"));
+        }
+        if let Some(executable_directory) = env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)) {
+            directories.push(executable_directory.clone());
+            directories.push(executable_directory.join("assets"));
+        }
+        Self::new(directories)
+    }
+
+    // absolute paths are used as they are, relative ones are tried against every directory
+    pub fn find(&self, path: &Path) -> Result<PathBuf> {
+        if path.is_absolute() {
+            return if path.is_file() { Ok(path.to_path_buf()) } else { Err(not_found(path, &[])) };
+        }
+        self.directories.iter()
+            .map(|directory| directory.join(path))
+            .find(|candidate| candidate.is_file())
+            .ok_or_else(|| not_found(path, &self.directories))
+    }
+
+    pub fn load_model(&self, path: &Path) -> Result<Mesh> {
+        load_model(&self.find(path)?)
+    }
+}
+
+// picks the loader from the file extension
+pub fn load_model(path: &Path) -> Result<Mesh> {
+    let extension = path.extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("obj") => load_obj(path),
+        Some("tris") => load_tris(path),
+        _ => Err(Error::format(format!("unsupported model format for {}, expected .obj or .tris", path.display()))),
+    }
+}
+
+fn not_found(path: &Path, directories: &[PathBuf]) -> Error {
+    let searched: Vec<String> = directories.iter().map(|directory| directory.display().to_string()).collect();
+    let message = if searched.is_empty() {
+        format!("{} does not exist", path.display())
+    } else {
+        format!("could not find {} in {}", path.display(), searched.join(", "))
+    };
+    Error::Io(io::Error::new(io::ErrorKind::NotFound, message))
+}
@@ -1,24 +1,148 @@
+use crate::framebuffer::DepthCompare;
+use crate::matrix::{Matrix4x4, translation_matrix};
+use crate::quaternion::Quaternion;
 use crate::vector3::Vector3;
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Projection {
+    Perspective,
+    // parallel rays for CAD style views, height is how many world units fit from the bottom to the top of the image
+    Orthographic { height: f32 },
+}
+
 pub struct Camera {
     pub position: Vector3,
-    pub rotation: Vector3,
+    pub rotation: Quaternion,
     pub h_fov: f32,
     pub v_fov: f32,
     pub z_near: f32,
     pub z_far: f32,
+    pub projection: Projection,
+    // store depth as 1 on the near plane and 0 on the far one, which keeps more precision far away
+    pub reversed_z: bool,
 }
 
 impl Camera {
-    pub fn new (position: Vector3, rotation: Vector3, h_fov: f32, v_fov: f32, z_near: f32, z_far: f32) -> Self {
-        Self { position, rotation, h_fov, v_fov, z_near, z_far }
+    pub fn new (position: Vector3, rotation: Quaternion, h_fov: f32, v_fov: f32, z_near: f32, z_far: f32) -> Self {
+        Self { position, rotation, h_fov, v_fov, z_near, z_far, projection: Projection::Perspective, reversed_z: false }
+    }
+
+    // a camera at position facing target, up picks which way is the top of the image
+    pub fn looking_at (position: Vector3, target: &Vector3, up: &Vector3, h_fov: f32, v_fov: f32, z_near: f32, z_far: f32) -> Self {
+        let mut camera = Self::new(position, Quaternion::identity(), h_fov, v_fov, z_near, z_far);
+        camera.turn_towards(target, up);
+        camera
     }
 
     pub fn translate(&mut self, x: f32, y: f32, z: f32) {
         self.position.translate(x, y, z);
     }
 
+    // turns around the world's up axis by y so the horizon stays level, then pitches by x and rolls by z around the camera's own axes
     pub fn rotate(&mut self, x: f32, y: f32, z: f32) {
-        self.rotation.translate(x, y, z);
+        let yaw = Quaternion::from_axis_angle(&Vector3::new(0.0, 1.0, 0.0), y);
+        let pitch = Quaternion::from_axis_angle(&Vector3::new(1.0, 0.0, 0.0), x);
+        let roll = Quaternion::from_axis_angle(&Vector3::new(0.0, 0.0, 1.0), z);
+        self.rotation = (yaw * self.rotation * pitch * roll).normalized();
+    }
+
+    // the same as looking_at for a camera that already exists, it stays where it is
+    pub fn turn_towards(&mut self, target: &Vector3, up: &Vector3) {
+        let forward = *target - self.position;
+        if forward.length_squared() > 0.0 {
+            self.rotation = Quaternion::look_rotation(&forward, up);
+        }
+    }
+
+    // orientation of the camera in world space
+    pub fn rotation_matrix(&self) -> Matrix4x4 {
+        self.rotation.to_matrix()
+    }
+
+    // view to world transform, the camera placed in the scene like any other object
+    pub fn world_matrix(&self) -> Matrix4x4 {
+        Matrix4x4::from_translation_rotation_scale(&self.position, &self.rotation, &Vector3::new(1.0, 1.0, 1.0))
+    }
+
+    // world to view transform, the inverse of world_matrix, a rotation is inverted by its transpose
+    pub fn view_matrix(&self) -> Matrix4x4 {
+        let inverse_translation = translation_matrix(-self.position.x, -self.position.y, -self.position.z);
+        self.rotation_matrix().transpose() * inverse_translation
+    }
+
+    // width / height of the view, the two fields of view set it for both kinds of projection
+    pub fn aspect_ratio(&self) -> f32 {
+        (self.h_fov.to_radians() / 2.0).tan() / (self.v_fov.to_radians() / 2.0).tan()
+    }
+
+    pub fn projection_matrix(&self) -> Matrix4x4 {
+        let projection_matrix = match self.projection {
+            Projection::Perspective => Matrix4x4::perspective(self.v_fov, self.aspect_ratio(), self.z_near, self.z_far),
+            Projection::Orthographic { height } => {
+                let half_height = height / 2.0;
+                let half_width = half_height * self.aspect_ratio();
+                Matrix4x4::orthographic(-half_width, half_width, -half_height, half_height, self.z_near, self.z_far)
+            },
+        };
+        if self.reversed_z { projection_matrix.reverse_depth() } else { projection_matrix }
+    }
+
+    pub fn depth_compare(&self) -> DepthCompare {
+        if self.reversed_z { DepthCompare::Greater } else { DepthCompare::Less }
+    }
+
+    // the orthographic height that shows things at this distance the same size as the perspective view does
+    pub fn orthographic_height(&self, distance: f32) -> f32 {
+        2.0 * distance * (self.v_fov.to_radians() / 2.0).tan()
     }
-}
\ No newline at end of file
+
+    // from the camera towards a world space point, an orthographic camera looks along the same direction everywhere
+    pub fn view_ray(&self, point: &Vector3) -> Vector3 {
+        match self.projection {
+            Projection::Perspective => *point - self.position,
+            Projection::Orthographic { .. } => self.forward(),
+        }
+    }
+
+    // unit vector from a surface point back to the viewer, for specular lighting
+    pub fn to_eye(&self, point: &Vector3) -> Vector3 {
+        -self.view_ray(point).normalized()
+    }
+
+    pub fn view_projection_matrix(&self) -> Matrix4x4 {
+        self.projection_matrix() * self.view_matrix()
+    }
+
+    // world space ray through the center of a pixel, starting on the near plane, for picking
+    pub fn screen_ray(&self, x: f32, y: f32, width: u32, height: u32) -> Option<(Vector3, Vector3)> {
+        let inverse = self.view_projection_matrix().inverse()?;
+        let ndc_x = (x + 0.5) / (width as f32 / 2.0) - 1.0;
+        let ndc_y = 1.0 - (y + 0.5) / (height as f32 / 2.0);
+        let (near_depth, far_depth) = if self.reversed_z { (1.0, 0.0) } else { (0.0, 1.0) };
+        let near = inverse.project_point(&Vector3::new(ndc_x, ndc_y, near_depth));
+        let far = inverse.project_point(&Vector3::new(ndc_x, ndc_y, far_depth));
+        Some((near, (far - near).normalized()))
+    }
+
+    // the camera looks down +z in view space
+    pub fn forward(&self) -> Vector3 {
+        self.direction(Vector3::new(0.0, 0.0, 1.0))
+    }
+
+    pub fn right(&self) -> Vector3 {
+        self.direction(Vector3::new(1.0, 0.0, 0.0))
+    }
+
+    pub fn up(&self) -> Vector3 {
+        self.direction(Vector3::new(0.0, 1.0, 0.0))
+    }
+
+    fn direction(&self, local: Vector3) -> Vector3 {
+        self.rotation_matrix().transform_direction(&local)
+    }
+}
+
+// the vertical field of view that keeps pixels square for a horizontal fov and width / height aspect ratio, in degrees
+pub fn vertical_fov(h_fov: f32, aspect: f32) -> f32 {
+    2.0 * ((h_fov.to_radians() / 2.0).tan() / aspect).atan().to_degrees()
+}
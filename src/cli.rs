@@ -0,0 +1,184 @@
+// hand rolled argument parsing for the renderer binary, see USAGE for the accepted options
+use std::path::PathBuf;
+
+pub const DEFAULT_WIDTH: u32 = 1920;
+pub const DEFAULT_HEIGHT: u32 = 1080;
+// the largest texture most SDL renderers accept, and a frame that still fits comfortably in memory
+pub const MAX_SIZE: u32 = 16384;
+
+pub const USAGE: &str = "\
+usage: nicks-software-renderer [options] [models.obj...] [scene.toml] [output.png]
+
+positional arguments are told apart by their extension, .obj and .tris files
+are models, a .toml file is a scene description and a .png, .ppm or .bmp file
+is rendered to disk without opening a window
+
+models and scenes are looked up in the working directory, next to the
+executable and in an assets folder in either of them
+
+options:
+  -o, --output <path>       render to an image file instead of opening a window
+  -s, --scene <path>        load a scene description file
+  -W, --width <pixels>      image or window width up to 16384, defaults to 1920
+  -H, --height <pixels>     image or window height up to 16384, defaults to 1080
+      --size <w>x<h>        width and height at once, e.g. 800x600
+      --fov <degrees>       horizontal field of view, the vertical one keeps pixels square
+      --near <distance>     near clipping plane
+      --far <distance>      far clipping plane
+  -b, --background <color>  background color as r,g,b or #rrggbb
+  -n, --frames <count>      stop after this many frames
+  -h, --help                print this message";
+
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub models: Vec<PathBuf>,
+    pub scene: Option<PathBuf>,
+    pub output: Option<PathBuf>,
+    pub width: u32,
+    pub height: u32,
+    // camera and background settings override the scene's own when given
+    pub fov: Option<f32>,
+    pub near: Option<f32>,
+    pub far: Option<f32>,
+    pub background: Option<(u8, u8, u8)>,
+    pub frames: Option<u32>,
+    pub help: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            models: Vec::new(),
+            scene: None,
+            output: None,
+            width: DEFAULT_WIDTH,
+            height: DEFAULT_HEIGHT,
+            fov: None,
+            near: None,
+            far: None,
+            background: None,
+            frames: None,
+            help: false,
+        }
+    }
+}
+
+impl Options {
+    pub fn aspect_ratio(&self) -> f32 {
+        self.width as f32 / self.height as f32
+    }
+}
+
+// the program name should already be skipped
+pub fn parse_args<I: IntoIterator<Item = String>> (args: I) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        // both "--width 800" and "--width=800" are accepted
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if arg.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+            _ => (arg.clone(), None),
+        };
+        let mut value = || inline_value.clone()
+            .or_else(|| args.next())
+            .ok_or_else(|| format!("{} needs a value", flag));
+
+        match flag.as_str() {
+            "-h" | "--help" => options.help = true,
+            "-o" | "--output" => options.output = Some(PathBuf::from(value()?)),
+            "-s" | "--scene" => options.scene = Some(PathBuf::from(value()?)),
+            "-W" | "--width" => options.width = parse_size(&flag, &value()?)?,
+            "-H" | "--height" => options.height = parse_size(&flag, &value()?)?,
+            "--size" => {
+                let size = value()?;
+                let (width, height) = size.split_once('x')
+                    .ok_or_else(|| format!("--size expects <width>x<height> but got \"{}\"", size))?;
+                options.width = parse_size(&flag, width)?;
+                options.height = parse_size(&flag, height)?;
+            },
+            "--fov" => {
+                let fov = parse_number(&flag, &value()?)?;
+                if fov <= 0.0 || fov >= 180.0 {
+                    return Err(format!("--fov must be between 0 and 180 degrees but got {}", fov));
+                }
+                options.fov = Some(fov);
+            },
+            "--near" => options.near = Some(parse_positive(&flag, &value()?)?),
+            "--far" => options.far = Some(parse_positive(&flag, &value()?)?),
+            "-b" | "--background" => options.background = Some(parse_color(&value()?)?),
+            "-n" | "--frames" => {
+                let frames = value()?;
+                options.frames = Some(frames.parse::<u32>()
+                    .ok()
+                    .filter(|&frames| frames > 0)
+                    .ok_or_else(|| format!("{} expects a positive whole number but got \"{}\"", flag, frames))?);
+            },
+            _ if flag.starts_with('-') && flag.len() > 1 => return Err(format!("unknown option {}", flag)),
+            _ => {
+                let path = PathBuf::from(&arg);
+                let extension = path.extension()
+                    .and_then(|extension| extension.to_str())
+                    .map(|extension| extension.to_ascii_lowercase());
+                match extension.as_deref() {
+                    Some("obj" | "tris") => options.models.push(path),
+                    Some("toml") => options.scene = Some(path),
+                    Some("png" | "ppm" | "bmp") => options.output = Some(path),
+                    _ => return Err(format!("don't know what to do with \"{}\", expected a .obj, .tris, .toml or image file", arg)),
+                }
+            },
+        }
+    }
+
+    if let (Some(near), Some(far)) = (options.near, options.far) {
+        if near >= far {
+            return Err(format!("--near ({}) must be closer than --far ({})", near, far));
+        }
+    }
+    Ok(options)
+}
+
+fn parse_number(flag: &str, value: &str) -> Result<f32, String> {
+    value.parse::<f32>()
+        .ok()
+        .filter(|number| number.is_finite())
+        .ok_or_else(|| format!("{} expects a number but got \"{}\"", flag, value))
+}
+
+fn parse_positive(flag: &str, value: &str) -> Result<f32, String> {
+    let number = parse_number(flag, value)?;
+    if number <= 0.0 {
+        return Err(format!("{} must be greater than zero but got {}", flag, number));
+    }
+    Ok(number)
+}
+
+fn parse_size(flag: &str, value: &str) -> Result<u32, String> {
+    let size = value.parse::<u32>()
+        .ok()
+        .filter(|&size| size > 0)
+        .ok_or_else(|| format!("{} expects a positive whole number of pixels but got \"{}\"", flag, value))?;
+    if size > MAX_SIZE {
+        return Err(format!("{} can be at most {} pixels but got {}", flag, MAX_SIZE, size));
+    }
+    Ok(size)
+}
+
+// accepts "r,g,b" with channels from 0 to 255 or a "#rrggbb" hex color
+pub fn parse_color(value: &str) -> Result<(u8, u8, u8), String> {
+    let invalid = || format!("expected a color as r,g,b or #rrggbb but got \"{}\"", value);
+    if let Some(hex) = value.strip_prefix('#') {
+        if hex.len() != 6 || !hex.is_ascii() {
+            return Err(invalid());
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid());
+        return Ok((channel(0)?, channel(2)?, channel(4)?));
+    }
+    let channels = value.split(',')
+        .map(|channel| channel.trim().parse::<u8>().map_err(|_| invalid()))
+        .collect::<Result<Vec<u8>, String>>()?;
+    match channels[..] {
+        [r, g, b] => Ok((r, g, b)),
+        _ => Err(invalid()),
+    }
+}
@@ -0,0 +1,35 @@
+use crate::cli::{Options, parse_args, parse_color};
+
+use std::path::PathBuf;
+
+#[test]
+fn parse_args_test () {
+    let args = |args: &[&str]| parse_args(args.iter().map(|arg| arg.to_string()));
+
+    assert_eq!(args(&[]).unwrap(), Options::default());
+
+    let options = args(&["cube.obj", "--size", "800x600", "--fov=90", "--near", "0.5", "--far", "50", "-b", "#ff8000", "-n", "3", "out.png"]).unwrap();
+    assert_eq!(options.models, vec![PathBuf::from("cube.obj")]);
+    assert_eq!((options.width, options.height), (800, 600));
+    assert_eq!(options.fov, Some(90.0));
+    assert_eq!((options.near, options.far), (Some(0.5), Some(50.0)));
+    assert_eq!(options.background, Some((255, 128, 0)));
+    assert_eq!(options.frames, Some(3));
+    assert_eq!(options.output, Some(PathBuf::from("out.png")));
+
+    let options = args(&["scenes/teapots.toml", "-W", "64", "-H", "32", "--background", "1, 2, 3"]).unwrap();
+    assert_eq!(options.scene, Some(PathBuf::from("scenes/teapots.toml")));
+    assert_eq!(options.aspect_ratio(), 2.0);
+    assert_eq!(options.background, Some((1, 2, 3)));
+
+    assert_eq!(args(&["--width"]).unwrap_err(), "--width needs a value");
+    assert_eq!(args(&["--width", "-3"]).unwrap_err(), "--width expects a positive whole number of pixels but got \"-3\"");
+    assert_eq!(args(&["--size", "70000x70000"]).unwrap_err(), "--size can be at most 16384 pixels but got 70000");
+    assert_eq!(args(&["--fov", "200"]).unwrap_err(), "--fov must be between 0 and 180 degrees but got 200");
+    assert_eq!(args(&["--near", "10", "--far", "1"]).unwrap_err(), "--near (10) must be closer than --far (1)");
+    assert_eq!(args(&["--frames", "0"]).unwrap_err(), "--frames expects a positive whole number but got \"0\"");
+    assert_eq!(args(&["--wat"]).unwrap_err(), "unknown option --wat");
+    assert!(args(&["model.stl"]).is_err());
+    assert!(parse_color("12,300,4").is_err());
+    assert!(parse_color("#12345").is_err());
+}
@@ -0,0 +1,91 @@
+// Sutherland-Hodgman clipping against the view frustum in homogeneous clip space.
+// After projection a point is visible when -w <= x <= w, -w <= y <= w and 0 <= z <= w.
+use crate::graphics::Varyings;
+use crate::vector4::Vector4;
+
+#[derive(Copy, Clone, Debug)]
+pub struct ClipVertex {
+    pub position: Vector4,
+    pub varyings: Varyings,
+    // true if the edge from this vertex to the next one in the polygon is part of the original triangle
+    pub edge: bool,
+}
+
+impl ClipVertex {
+    pub fn new(position: Vector4) -> Self {
+        Self::with_varyings(position, Varyings::default())
+    }
+
+    pub fn with_varyings(position: Vector4, varyings: Varyings) -> Self {
+        Self { position, varyings, edge: true }
+    }
+
+    pub fn lerp(&self, other: &ClipVertex, t: f32) -> ClipVertex {
+        ClipVertex { position: self.position.lerp(&other.position, t), varyings: self.varyings.lerp(&other.varyings, t), edge: self.edge }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ClipPlane {
+    Near,
+    Far,
+    Left,
+    Right,
+    Bottom,
+    Top,
+}
+
+pub const CLIP_PLANES: [ClipPlane; 6] = [
+    ClipPlane::Near,
+    ClipPlane::Far,
+    ClipPlane::Left,
+    ClipPlane::Right,
+    ClipPlane::Bottom,
+    ClipPlane::Top,
+];
+
+impl ClipPlane {
+    // signed distance to the plane, positive on the visible side
+    pub fn distance(&self, position: &Vector4) -> f32 {
+        let Vector4 { x, y, z, w } = *position;
+        match self {
+            ClipPlane::Near => z,
+            ClipPlane::Far => w - z,
+            ClipPlane::Left => w + x,
+            ClipPlane::Right => w - x,
+            ClipPlane::Bottom => w + y,
+            ClipPlane::Top => w - y,
+        }
+    }
+}
+
+pub fn clip_triangle(vertices: [ClipVertex; 3]) -> Vec<ClipVertex> {
+    let mut polygon = vertices.to_vec();
+    for plane in CLIP_PLANES {
+        if polygon.is_empty() {
+            break;
+        }
+        polygon = clip_polygon(&polygon, plane);
+    }
+    polygon
+}
+
+pub fn clip_polygon(polygon: &[ClipVertex], plane: ClipPlane) -> Vec<ClipVertex> {
+    let mut output = Vec::with_capacity(polygon.len() + 1);
+    for (i, current) in polygon.iter().enumerate() {
+        let next = &polygon[(i + 1) % polygon.len()];
+        let d1 = plane.distance(&current.position);
+        let d2 = plane.distance(&next.position);
+        if d1 >= 0.0 {
+            output.push(*current);
+        }
+        if (d1 >= 0.0) != (d2 >= 0.0) {
+            let mut intersection = current.lerp(next, d1 / (d1 - d2));
+            // leaving the plane means the next edge runs along the plane, not the triangle
+            intersection.edge = d1 < 0.0 && current.edge;
+            output.push(intersection);
+        }
+    }
+    output
+}
+
@@ -0,0 +1,119 @@
+// camera controllers turn mouse and keyboard movement into camera placement, they hold no window state
+// so the viewer feeds them deltas and they write the result into a Camera
+use crate::camera::{Camera, Projection};
+use crate::quaternion::{EulerOrder, Quaternion};
+use crate::vector3::Vector3;
+
+use std::f32::consts::FRAC_PI_2;
+
+// radians per pixel of mouse movement
+const MOUSE_SENSITIVITY: f32 = 0.005;
+// each wheel notch moves this fraction of the way towards the target
+const ZOOM_STEP: f32 = 0.1;
+const MIN_DISTANCE: f32 = 0.01;
+// a little short of straight up or down, where yaw stops meaning anything
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+fn world_up() -> Vector3 {
+    Vector3::new(0.0, 1.0, 0.0)
+}
+
+// yaw around the world's up axis and pitch above or below the horizon that face along direction
+fn yaw_pitch(direction: &Vector3) -> (f32, f32) {
+    let direction = direction.normalized();
+    (direction.x.atan2(direction.z), (-direction.y).clamp(-1.0, 1.0).asin().clamp(-MAX_PITCH, MAX_PITCH))
+}
+
+fn yaw_pitch_rotation(yaw: f32, pitch: f32) -> Quaternion {
+    Quaternion::from_axis_angle(&world_up(), yaw) * Quaternion::from_axis_angle(&Vector3::new(1.0, 0.0, 0.0), pitch)
+}
+
+// circles the camera around a target, dragging turns it around the target and the wheel moves it closer or further
+pub struct OrbitController {
+    pub target: Vector3,
+    pub distance: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+    // around the view direction, only a camera that started out tilted has any
+    pub roll: f32,
+}
+
+impl OrbitController {
+    pub fn new(target: Vector3, distance: f32) -> Self {
+        Self { target, distance: distance.max(MIN_DISTANCE), yaw: 0.0, pitch: 0.0, roll: 0.0 }
+    }
+
+    // keeps the camera where it is and turns it to face the target
+    pub fn from_camera(camera: &Camera, target: Vector3) -> Self {
+        let offset = target - camera.position;
+        let (yaw, pitch) = if offset.length_squared() > 0.0 { yaw_pitch(&offset) } else { yaw_pitch(&camera.forward()) };
+        Self { target, distance: offset.length().max(MIN_DISTANCE), yaw, pitch, roll: 0.0 }
+    }
+
+    // circles the point distance ahead of the camera, applying it leaves the camera exactly as it was
+    pub fn from_view(camera: &Camera, distance: f32) -> Self {
+        let distance = distance.max(MIN_DISTANCE);
+        // yaw around y, then pitch around x, then roll around z is the same order apply puts them back together in
+        let angles = camera.rotation.to_euler(EulerOrder::Zxy);
+        Self { target: camera.position + camera.forward() * distance, distance, yaw: angles.y, pitch: angles.x, roll: angles.z }
+    }
+
+    // mouse movement in pixels, dragging right swings the camera to the left so the target seems to turn right
+    pub fn drag(&mut self, dx: f32, dy: f32) {
+        self.yaw += dx * MOUSE_SENSITIVITY;
+        self.pitch = (self.pitch + dy * MOUSE_SENSITIVITY).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    // positive steps zoom in, like scrolling the wheel away from you
+    pub fn zoom(&mut self, steps: f32) {
+        self.distance = (self.distance * (1.0 - ZOOM_STEP).powf(steps)).max(MIN_DISTANCE);
+    }
+
+    pub fn apply(&self, camera: &mut Camera) {
+        let rotation = yaw_pitch_rotation(self.yaw, self.pitch) * Quaternion::from_axis_angle(&Vector3::new(0.0, 0.0, 1.0), self.roll);
+        camera.position = self.target - rotation.rotate(&Vector3::new(0.0, 0.0, self.distance));
+        camera.rotation = rotation;
+        // an orthographic view has no perspective to shrink things, so zooming changes how much of the world fits instead
+        if let Projection::Orthographic { .. } = camera.projection {
+            camera.projection = Projection::Orthographic { height: camera.orthographic_height(self.distance) };
+        }
+    }
+}
+
+// first person movement, the mouse turns the camera and movement follows where it is looking
+pub struct FlyController {
+    pub yaw: f32,
+    pub pitch: f32,
+    // world units per call to fly with a full input
+    pub speed: f32,
+}
+
+impl FlyController {
+    pub fn new(speed: f32) -> Self {
+        Self { yaw: 0.0, pitch: 0.0, speed }
+    }
+
+    // starts from the direction the camera already faces, any roll is dropped
+    pub fn from_camera(camera: &Camera, speed: f32) -> Self {
+        let (yaw, pitch) = yaw_pitch(&camera.forward());
+        Self { yaw, pitch, speed }
+    }
+
+    // mouse movement in pixels, moving the mouse down looks down
+    pub fn look(&mut self, dx: f32, dy: f32) {
+        self.yaw += dx * MOUSE_SENSITIVITY;
+        self.pitch = (self.pitch + dy * MOUSE_SENSITIVITY).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    // inputs from -1 to 1, forward and right follow the view while up is always the world's up
+    pub fn fly(&self, camera: &mut Camera, forward: f32, right: f32, up: f32) {
+        let rotation = yaw_pitch_rotation(self.yaw, self.pitch);
+        let forward = rotation.rotate(&Vector3::new(0.0, 0.0, forward));
+        let right = rotation.rotate(&Vector3::new(right, 0.0, 0.0));
+        camera.position += (forward + right + world_up() * up) * self.speed;
+    }
+
+    pub fn apply(&self, camera: &mut Camera) {
+        camera.rotation = yaw_pitch_rotation(self.yaw, self.pitch);
+    }
+}
@@ -0,0 +1,58 @@
+use std::fmt;
+use std::io;
+
+// everything that can go wrong while loading assets or producing a frame
+#[derive(Debug)]
+pub enum Error {
+    // reading or writing a file failed
+    Io(io::Error),
+    // a text file has a mistake on this line, line numbers start at 1
+    Parse { line: usize, message: String },
+    // a file isn't in a format the renderer understands or its contents are broken
+    Format(String),
+    // the render target could not be created or written to, or the scene can't be drawn as it was put together
+    Render(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn parse(line: usize, message: impl Into<String>) -> Self {
+        Error::Parse { line, message: message.into() }
+    }
+
+    pub fn format(message: impl Into<String>) -> Self {
+        Error::Format(message.into())
+    }
+
+    // window and texture libraries report failures in their own types, only the message is kept
+    pub fn render(error: impl fmt::Display) -> Self {
+        Error::Render(error.to_string())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "{}", error),
+            Error::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            Error::Format(message) => write!(f, "{}", message),
+            Error::Render(message) => write!(f, "render failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
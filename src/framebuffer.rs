@@ -0,0 +1,111 @@
+use crate::error::{Error, Result};
+
+use std::mem;
+
+// Color and depth targets owned by the renderer. Colors are stored as tightly packed RGB24 rows,
+// which is the layout SDL's RGB24 textures and most image formats expect.
+pub struct Framebuffer {
+    pub width: u32,
+    pub height: u32,
+    pub color: Vec<u8>,
+    pub depth: Vec<f32>,
+    pub depth_compare: DepthCompare,
+}
+
+// which of two depths is closer to the camera, reversed-z projections put the near plane at 1 and the far one at 0
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum DepthCompare {
+    #[default]
+    Less,
+    Greater,
+}
+
+impl DepthCompare {
+    // further away than anything that can be drawn
+    pub fn clear_value(self) -> f32 {
+        match self {
+            DepthCompare::Less => f32::INFINITY,
+            DepthCompare::Greater => f32::NEG_INFINITY,
+        }
+    }
+
+    pub fn is_closer(self, z: f32, stored: f32) -> bool {
+        match self {
+            DepthCompare::Less => z < stored,
+            DepthCompare::Greater => z > stored,
+        }
+    }
+
+    // moves z a little towards the camera
+    pub fn bias(self, z: f32, amount: f32) -> f32 {
+        match self {
+            DepthCompare::Less => z - amount,
+            DepthCompare::Greater => z + amount,
+        }
+    }
+}
+
+impl Framebuffer {
+    pub fn new(width: u32, height: u32) -> Result<Self> {
+        // the depth buffer is the larger of the two, if its size fits so does the color buffer's
+        let pixels = (width as usize).checked_mul(height as usize)
+            .filter(|&pixels| pixels > 0 && pixels.checked_mul(mem::size_of::<f32>()).is_some())
+            .ok_or_else(|| Error::Render(format!("can't render a {}x{} image", width, height)))?;
+        Ok(Self {
+            width,
+            height,
+            color: vec![0; pixels * 3],
+            depth: vec![f32::INFINITY; pixels],
+            depth_compare: DepthCompare::Less,
+        })
+    }
+
+    pub fn clear(&mut self, color: (u8, u8, u8)) {
+        for pixel in self.color.chunks_exact_mut(3) {
+            pixel.copy_from_slice(&[color.0, color.1, color.2]);
+        }
+        self.clear_depth();
+    }
+
+    pub fn clear_depth(&mut self) {
+        self.depth.fill(self.depth_compare.clear_value());
+    }
+
+    // bytes per row of the color buffer
+    pub fn pitch(&self) -> usize {
+        self.width as usize * 3
+    }
+
+    pub fn pixel(&self, x: i32, y: i32) -> Option<(u8, u8, u8)> {
+        self.index(x, y).map(|i| (self.color[i * 3], self.color[i * 3 + 1], self.color[i * 3 + 2]))
+    }
+
+    pub fn set_pixel(&mut self, x: i32, y: i32, color: (u8, u8, u8)) {
+        if let Some(i) = self.index(x, y) {
+            self.color[i * 3..i * 3 + 3].copy_from_slice(&[color.0, color.1, color.2]);
+        }
+    }
+
+    pub fn depth(&self, x: i32, y: i32) -> Option<f32> {
+        self.index(x, y).map(|i| self.depth[i])
+    }
+
+    // stores z and returns true if it is closer than what is already at (x, y)
+    pub fn test_and_set_depth(&mut self, x: i32, y: i32, z: f32) -> bool {
+        match self.index(x, y) {
+            Some(i) if self.depth_compare.is_closer(z, self.depth[i]) => {
+                self.depth[i] = z;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            None
+        } else {
+            Some(y as usize * self.width as usize + x as usize)
+        }
+    }
+}
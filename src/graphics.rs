@@ -1,130 +1,310 @@
-use crate::matrix::{Matrix4x4, matrix_vector_multiply};
-use crate::mesh::Mesh;
+use crate::matrix::Matrix4x4;
+use crate::mesh::{Mesh, transform_normal, transform_point};
+use crate::indexed_mesh::IndexedMesh;
 use crate::camera::Camera;
-use crate::triangle::calculate_normal;
-use crate::vector3::{subtract, dot_product};
-
-use sdl2::rect::Point;
-use sdl2::render::Canvas;
-use sdl2::pixels::Color;
-
-pub fn project <T: sdl2::render::RenderTarget> (canvas: &mut Canvas<T>, camera: &Camera, mesh: &Mesh, color: (u8, u8, u8)) {
-    let size = canvas.output_size().unwrap();
-    let half_width = size.0 as f32 / 2.0;
-    let half_height = size.1 as f32 / 2.0;
-
-    let hf = 1.0 / (camera.h_fov.to_radians() / 2.0).tan();
-    let vf = 1.0 / (camera.v_fov.to_radians() / 2.0).tan();
-    let lambda = camera.z_far / (camera.z_far - camera.z_near);
-
-    //  1/tan(hfov/2)              0                  0                           0
-    //              0  1/tan(vfov/2)                  0                           0
-    //              0              0  zfar/(zfar-znear)  -znear * zfar/(zfar-znear)
-    //              0              0                  1                           0
-    let mut projection_matrix = Matrix4x4 { m: [[0.0; 4]; 4] };
-    projection_matrix.m[0][0] = hf;
-    projection_matrix.m[1][1] = vf;
-    projection_matrix.m[2][2] = lambda;
-    projection_matrix.m[2][3] = -camera.z_near * lambda;
-    projection_matrix.m[3][2] = 1.0;
-
-    'triangle: for triangle in &mesh.triangles {
-        let normal = calculate_normal(triangle);
-        let camera_ray = subtract(&triangle.vertices[0], &camera.position);
-        if dot_product(&normal, &camera_ray) < 0.0 {
-            let mut points = [Point::new(0, 0); 4];
-            for (i, vertex) in triangle.vertices.iter().enumerate() {
-                let point = matrix_vector_multiply(&projection_matrix, vertex);
-                let mut x = point[0][0];
-                let mut y = -point[1][0];
-                let mut z = point[2][0];
-                let w = point[3][0];
-
-                // if w is less than 1 it rapidly scales the x/y/z values
-                if w > 1.0 {
-                    x /= w;
-                    y /= w;
-                    z /= w;
-                } else {
-                    break 'triangle;
+use crate::clipping::{ClipVertex, clip_triangle};
+use crate::triangle::{Triangle, calculate_normal};
+use crate::vector2::Vector2;
+use crate::vector3::Vector3;
+use crate::vector4::Vector4;
+
+use crate::framebuffer::Framebuffer;
+use crate::light::{LightSample, Lighting, to_rgb};
+use crate::material::{Material, Shading};
+
+// outlines sit exactly on the filled surface, so they need a little slack to win the depth test
+const OUTLINE_DEPTH_BIAS: f32 = 0.0001;
+
+// sub pixel bits of the fixed point screen positions the rasterizer works with, 1/256 of a pixel
+const SUBPIXEL_BITS: u32 = 8;
+const SUBPIXEL_SCALE: f32 = (1 << SUBPIXEL_BITS) as f32;
+// keeps the products of fixed point coordinates well inside an i64
+const MAX_SCREEN_COORDINATE: f32 = (1 << 20) as f32;
+
+// pixel centers sit on whole numbers, so (3.0, 5.0) is the middle of pixel (3, 5)
+#[derive(Copy, Clone, Debug)]
+pub struct ScreenPoint {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    // clip space w, needed for perspective correct interpolation
+    pub w: f32,
+}
+
+impl ScreenPoint {
+    pub fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z, w: 1.0 }
+    }
+}
+
+// per vertex values that are interpolated across a triangle
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Varyings {
+    pub world_position: Vector3,
+    pub normal: Vector3,
+    // gouraud shading lights the vertices and interpolates the result
+    pub light: LightSample,
+    pub uv: Vector2,
+}
+
+impl Varyings {
+    pub fn lerp(&self, other: &Varyings, t: f32) -> Varyings {
+        Varyings {
+            world_position: self.world_position.lerp(&other.world_position, t),
+            normal: self.normal.lerp(&other.normal, t),
+            light: LightSample {
+                diffuse: self.light.diffuse.lerp(&other.light.diffuse, t),
+                specular: self.light.specular.lerp(&other.light.specular, t),
+            },
+            uv: self.uv.lerp(&other.uv, t),
+        }
+    }
+
+    pub fn interpolate(vertices: [&Varyings; 3], weights: [f32; 3]) -> Varyings {
+        let mut result = Varyings::default();
+        for (vertex, weight) in vertices.iter().zip(weights) {
+            result.world_position += vertex.world_position * weight;
+            result.normal += vertex.normal * weight;
+            result.light.diffuse += vertex.light.diffuse * weight;
+            result.light.specular += vertex.light.specular * weight;
+            result.uv += vertex.uv * weight;
+        }
+        result
+    }
+}
+
+struct TransformedVertex {
+    clip: Vector4,
+    varyings: Varyings,
+    has_normal: bool,
+    has_uv: bool,
+}
+
+pub fn project (framebuffer: &mut Framebuffer, camera: &Camera, mesh: &Mesh, material: &Material, lighting: &Lighting) {
+    project_geometry(framebuffer, camera, &mesh.geometry, &mesh.model_matrix(), &mesh.normal_matrix(), material, lighting);
+}
+
+// draws object space geometry with an explicit object to world transform, e.g. one inherited from a parent node
+pub fn project_geometry (framebuffer: &mut Framebuffer, camera: &Camera, geometry: &IndexedMesh, model_matrix: &Matrix4x4, normal_matrix: &Matrix4x4, material: &Material, lighting: &Lighting) {
+    let half_width = framebuffer.width as f32 / 2.0;
+    let half_height = framebuffer.height as f32 / 2.0;
+
+    let view_projection_matrix = camera.view_projection_matrix();
+
+    // post-transform vertex cache, every unique vertex is transformed and lit once per draw
+    let transformed: Vec<TransformedVertex> = geometry.vertices.iter().map(|vertex| {
+        let world_position = transform_point(model_matrix, &vertex.position);
+        let mut varyings = Varyings { world_position, uv: vertex.uv.unwrap_or_default(), ..Varyings::default() };
+        if let Some(normal) = &vertex.normal {
+            varyings.normal = transform_normal(normal_matrix, normal);
+            if material.shading == Shading::Gouraud {
+                varyings.light = lighting.sample(&varyings.normal, &camera.to_eye(&world_position), material);
+            }
+        }
+        TransformedVertex {
+            clip: view_projection_matrix * Vector4::from_point(&world_position),
+            varyings,
+            has_normal: vertex.normal.is_some(),
+            has_uv: vertex.uv.is_some(),
+        }
+    }).collect();
+
+    for indices in geometry.indices.chunks_exact(3) {
+        let corners = [0, 1, 2].map(|i| &transformed[indices[i] as usize]);
+        let triangle = Triangle::new(corners.map(|corner| corner.varyings.world_position));
+
+        let normal = calculate_normal(&triangle);
+        if normal.dot(&camera.view_ray(&triangle.vertices[0])) >= 0.0 {
+            continue;
+        }
+
+        let mut clip_vertices = corners.map(|corner| ClipVertex::with_varyings(corner.clip, corner.varyings));
+        // without a normal on every corner the triangle is lit with its face normal
+        if !corners.iter().all(|corner| corner.has_normal) {
+            for vertex in &mut clip_vertices {
+                vertex.varyings.normal = normal;
+                if material.shading == Shading::Gouraud {
+                    vertex.varyings.light = lighting.sample(&normal, &camera.to_eye(&vertex.varyings.world_position), material);
                 }
+            }
+        }
 
-                if x.abs() > 1.0 || y.abs() > 1.0 || z.abs() > 1.0 {
-                    break 'triangle;
+        let polygon = clip_triangle(clip_vertices);
+        if polygon.len() < 3 {
+            continue;
+        }
+
+        let points: Vec<ScreenPoint> = polygon.iter()
+            .map(|vertex| to_screen(vertex, half_width, half_height))
+            .collect();
+
+        let centroid = (triangle.vertices[0] + triangle.vertices[1] + triangle.vertices[2]) / 3.0;
+        let flat_light = lighting.sample(&normal, &camera.to_eye(&centroid), material);
+        let flat_color = to_rgb(&flat_light.apply(&material.base_color()));
+        let textured = material.texture.is_some() && corners.iter().all(|corner| corner.has_uv);
+        for i in 1..points.len() - 1 {
+            let corners = [&polygon[0].varyings, &polygon[i].varyings, &polygon[i + 1].varyings];
+            let mut fragment = |weights: [f32; 3]| {
+                if material.shading == Shading::Flat && !textured {
+                    return flat_color;
                 }
-                x = x * half_width + half_width;
-                y = y * half_height + half_height;
-                points[i] = Point::new(x as i32, y as i32);
+                let varyings = Varyings::interpolate(corners, weights);
+                let light = match material.shading {
+                    Shading::Flat => flat_light,
+                    Shading::Gouraud => varyings.light,
+                    Shading::Phong => lighting.sample(&varyings.normal.normalized(), &camera.to_eye(&varyings.world_position), material),
+                };
+                let albedo = material.albedo(if textured { Some(varyings.uv) } else { None });
+                to_rgb(&light.apply(&albedo))
+            };
+            fill_triangle_with(framebuffer, [points[0], points[i], points[i + 1]], &mut fragment);
+        }
+        if !material.outline {
+            continue;
+        }
+        for (i, vertex) in polygon.iter().enumerate() {
+            if vertex.edge {
+                draw_line(framebuffer, points[i], points[(i + 1) % points.len()], (0, 0, 0));
             }
-            points[3] = points[0];
-            canvas.set_draw_color(Color::RGB(color.0, color.1, color.2));
-            fill_triangle(canvas, points[0], points[1], points[2]);
-            canvas.set_draw_color(Color::RGB(0, 0, 0));
-            canvas.draw_lines(&points[..]).unwrap();
         }
     }
 }
 
-// http://www.sunshine2k.de/coding/java/TriangleRasterization/TriangleRasterization.html
-pub fn fill_triangle <T: sdl2::render::RenderTarget> (canvas: &mut Canvas<T>, v1: Point, v2: Point, v3: Point) {
-    let mut vrts = [v1, v2, v3];
+// perspective divide followed by the viewport transform, shifted by half a pixel so pixel centers land on whole numbers
+fn to_screen(vertex: &ClipVertex, half_width: f32, half_height: f32) -> ScreenPoint {
+    let ndc = vertex.position.perspective_divide();
+    ScreenPoint {
+        x: ndc.x * half_width + half_width - 0.5,
+        y: -ndc.y * half_height + half_height - 0.5,
+        z: ndc.z,
+        w: vertex.position.w,
+    }
+}
+
+// depth tested bresenham line, used for the triangle outlines
+pub fn draw_line (framebuffer: &mut Framebuffer, v1: ScreenPoint, v2: ScreenPoint, color: (u8, u8, u8)) {
+    let (x1, y1) = (v1.x.round() as i32, v1.y.round() as i32);
+    let (x2, y2) = (v2.x.round() as i32, v2.y.round() as i32);
+    let dx = (x2 - x1).abs();
+    let dy = -(y2 - y1).abs();
+    let sx = if x1 < x2 { 1 } else { -1 };
+    let sy = if y1 < y2 { 1 } else { -1 };
+    let steps = dx.max(-dy).max(1) as f32;
+
+    let mut x = x1;
+    let mut y = y1;
+    let mut error = dx + dy;
+    let mut step = 0.0;
     loop {
-        if vrts[0].y <= vrts[1].y && vrts[1].y <= vrts[2].y {
+        let z = framebuffer.depth_compare.bias(v1.z + (v2.z - v1.z) * (step / steps), OUTLINE_DEPTH_BIAS);
+        if framebuffer.test_and_set_depth(x, y, z) {
+            framebuffer.set_pixel(x, y, color);
+        }
+        if x == x2 && y == y2 {
             break;
-        } else if vrts[1].y < vrts[0].y {
-            vrts.swap(0, 1);
-        } else if vrts[2].y < vrts[1].y {
-            vrts.swap(1, 2);
         }
-    }
-    let v1 = vrts[0];
-    let v2 = vrts[1];
-    let v3 = vrts[2];
-
-    if v2.y == v3.y {
-        fill_bottom_flat_triangle(canvas, v1, v2, v3);
-    } else if v1.y == v2.y {
-        fill_top_flat_triangle(canvas, v1, v2, v3);
-    } else {
-        let v4 = Point::new(
-            (v1.x as f32 + ((v2.y - v1.y) as f32 / (v3.y - v1.y) as f32) * (v3.x - v1.x) as f32) as i32,
-            v2.y,
-        );
-        fill_bottom_flat_triangle(canvas, v1, v2, v4);
-        fill_top_flat_triangle(canvas, v2, v4, v3);
+        let e2 = 2 * error;
+        if e2 >= dy {
+            error += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            error += dx;
+            y += sy;
+        }
+        step += 1.0;
     }
 }
 
-// http://www.sunshine2k.de/coding/java/TriangleRasterization/TriangleRasterization.html
-fn fill_bottom_flat_triangle<T: sdl2::render::RenderTarget> (canvas: &mut Canvas<T>, v1: Point, v2: Point, v3: Point) {
-    let inv_slope1 = (v2.x - v1.x) as f32 / (v2.y - v1.y) as f32;
-    let inv_slope2 = (v3.x - v1.x) as f32 / (v3.y - v1.y) as f32;
-    let mut cursor1 = v1.x as f32;
-    let mut cursor2 = v1.x as f32;
-    let mut scanline = v1.y as f32;
-    while scanline <= v2.y as f32 {
-        let p1 = Point::new(cursor1 as i32, scanline as i32);
-        let p2 = Point::new(cursor2 as i32, scanline as i32);
-        canvas.draw_line(p1, p2).unwrap();
-        cursor1 += inv_slope1;
-        cursor2 += inv_slope2;
-        scanline += 1.0;
-    }
+// fills the triangle with a single color
+pub fn fill_triangle (framebuffer: &mut Framebuffer, v1: ScreenPoint, v2: ScreenPoint, v3: ScreenPoint, color: (u8, u8, u8)) {
+    fill_triangle_with(framebuffer, [v1, v2, v3], &mut |_| color);
+}
+
+// the fragment function receives the perspective correct barycentric weights of each visible pixel
+pub fn fill_triangle_with (framebuffer: &mut Framebuffer, points: [ScreenPoint; 3], fragment: &mut dyn FnMut([f32; 3]) -> (u8, u8, u8)) {
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    rasterize_triangle(&points, width, height, &mut |x, y, b| {
+        // depth is already divided by w so it interpolates linearly in screen space
+        let z = b[0] * points[0].z + b[1] * points[1].z + b[2] * points[2].z;
+        if !framebuffer.test_and_set_depth(x, y, z) {
+            return;
+        }
+        let mut weights = [b[0] / points[0].w, b[1] / points[1].w, b[2] / points[2].w];
+        let sum = weights[0] + weights[1] + weights[2];
+        for weight in &mut weights {
+            *weight /= sum;
+        }
+        framebuffer.set_pixel(x, y, fragment(weights));
+    });
 }
 
-// http://www.sunshine2k.de/coding/java/TriangleRasterization/TriangleRasterization.html
-fn fill_top_flat_triangle<T: sdl2::render::RenderTarget> (canvas: &mut Canvas<T>, v1: Point, v2: Point, v3: Point) {
-    let inv_slope1 = (v3.x - v1.x) as f32 / (v3.y - v1.y) as f32;
-    let inv_slope2 = (v3.x - v2.x) as f32 / (v3.y - v2.y) as f32;
-    let mut cursor1 = v3.x as f32;
-    let mut cursor2 = v3.x as f32;
-    let mut scanline = v3.y as f32;
-    while scanline >= v1.y as f32 {
-        let p1 = Point::new(cursor1 as i32, scanline as i32);
-        let p2 = Point::new(cursor2 as i32, scanline as i32);
-        canvas.draw_line(p1, p2).unwrap();
-        cursor1 -= inv_slope1;
-        cursor2 -= inv_slope2;
-        scanline -= 1.0;
+// calls visit with the screen space barycentric weights of every pixel center inside the triangle, either winding works.
+// edges are tested in fixed point with the top-left rule, a pixel center exactly on an edge belongs to the triangle
+// only if that is a top edge (horizontal, with the triangle below it) or a left edge, so a pixel on an edge shared
+// by two triangles is drawn exactly once. triangles without area are skipped
+pub fn rasterize_triangle (points: &[ScreenPoint; 3], width: u32, height: u32, visit: &mut dyn FnMut(i32, i32, [f32; 3])) {
+    // the pipeline clips to the screen first, anything this far off it can only be a broken input and would overflow
+    if points.iter().any(|point| !(point.x.abs() < MAX_SCREEN_COORDINATE && point.y.abs() < MAX_SCREEN_COORDINATE)) {
+        return;
     }
-}
\ No newline at end of file
+    let fixed = points.map(|point| ((point.x * SUBPIXEL_SCALE).round() as i64, (point.y * SUBPIXEL_SCALE).round() as i64));
+    let area = edge_function(fixed[0], fixed[1], fixed[2]);
+    if area == 0 {
+        return;
+    }
+    // walk the corners so the inside of every edge is positive, order maps the weights back to the caller's corners
+    let order = if area > 0 { [0, 1, 2] } else { [0, 2, 1] };
+    let v = order.map(|i| fixed[i]);
+    let area = area.abs();
+
+    let one = 1 << SUBPIXEL_BITS;
+    let min_x = v.iter().map(|p| p.0).min().unwrap_or(0);
+    let max_x = v.iter().map(|p| p.0).max().unwrap_or(0);
+    let min_y = v.iter().map(|p| p.1).min().unwrap_or(0);
+    let max_y = v.iter().map(|p| p.1).max().unwrap_or(0);
+    // first and last pixel centers inside the bounding box, cut down to the framebuffer
+    let x_start = (min_x + one - 1).div_euclid(one).max(0);
+    let x_end = max_x.div_euclid(one).min(width as i64 - 1);
+    let y_start = (min_y + one - 1).div_euclid(one).max(0);
+    let y_end = max_y.div_euclid(one).min(height as i64 - 1);
+    if x_start > x_end || y_start > y_end {
+        return;
+    }
+
+    // edge i runs between the two corners that aren't corner i, so its value is corner i's weight
+    let edges = [(v[1], v[2]), (v[2], v[0]), (v[0], v[1])];
+    let bias = edges.map(|(a, b)| if is_top_left(a, b) { 0 } else { -1 });
+    let step_x = edges.map(|(a, b)| -(b.1 - a.1) * one);
+    let step_y = edges.map(|(a, b)| (b.0 - a.0) * one);
+    let mut row = edges.map(|(a, b)| edge_function(a, b, (x_start * one, y_start * one)));
+
+    for y in y_start..=y_end {
+        let mut e = row;
+        for x in x_start..=x_end {
+            if (0..3).all(|i| e[i] + bias[i] >= 0) {
+                let mut weights = [0.0; 3];
+                for i in 0..3 {
+                    weights[order[i]] = e[i] as f32 / area as f32;
+                }
+                visit(x as i32, y as i32, weights);
+            }
+            for i in 0..3 {
+                e[i] += step_x[i];
+            }
+        }
+        for i in 0..3 {
+            row[i] += step_y[i];
+        }
+    }
+}
+
+// twice the signed area of the triangle a, b, p in fixed point, positive when p is on the inside of a to b
+fn edge_function(a: (i64, i64), b: (i64, i64), p: (i64, i64)) -> i64 {
+    (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0)
+}
+
+// with the inside on the positive side and y pointing down, top edges run to the right and left edges run up
+fn is_top_left(a: (i64, i64), b: (i64, i64)) -> bool {
+    (a.1 == b.1 && b.0 > a.0) || b.1 < a.1
+}
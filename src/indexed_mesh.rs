@@ -0,0 +1,92 @@
+use crate::error::{Error, Result};
+use crate::triangle::Triangle;
+use crate::vector2::Vector2;
+use crate::vector3::Vector3;
+
+use std::collections::HashMap;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Vertex {
+    pub position: Vector3,
+    pub normal: Option<Vector3>,
+    pub uv: Option<Vector2>,
+}
+
+impl Vertex {
+    pub fn new(position: Vector3) -> Self {
+        Self { position, normal: None, uv: None }
+    }
+
+    // vertices are only merged when every attribute matches bit for bit
+    fn key(&self) -> [u32; 8] {
+        let normal = self.normal.map_or([u32::MAX; 3], |n| [bits(n.x), bits(n.y), bits(n.z)]);
+        let uv = self.uv.map_or([u32::MAX; 2], |uv| [bits(uv.x), bits(uv.y)]);
+        let p = &self.position;
+        [bits(p.x), bits(p.y), bits(p.z), normal[0], normal[1], normal[2], uv[0], uv[1]]
+    }
+}
+
+// treats -0.0 and 0.0 as the same value
+fn bits(value: f32) -> u32 {
+    (value + 0.0).to_bits()
+}
+
+// a vertex buffer plus three indices per triangle, so shared vertices are stored and transformed once
+#[derive(Clone, Debug, Default)]
+pub struct IndexedMesh {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+}
+
+impl IndexedMesh {
+    pub fn new(vertices: Vec<Vertex>, indices: Vec<u32>) -> Result<Self> {
+        if !indices.len().is_multiple_of(3) {
+            return Err(Error::format(format!("{} indices don't make whole triangles", indices.len())));
+        }
+        if let Some(index) = indices.iter().find(|&&i| i as usize >= vertices.len()) {
+            return Err(Error::format(format!("index {} is out of range for {} vertices", index, vertices.len())));
+        }
+        Ok(Self { vertices, indices })
+    }
+
+    pub fn from_triangles(triangles: &[Triangle]) -> Self {
+        let mut mesh = IndexedMesh::default();
+        let mut lookup: HashMap<[u32; 8], u32> = HashMap::new();
+        for triangle in triangles {
+            for i in 0..3 {
+                let vertex = Vertex {
+                    position: triangle.vertices[i],
+                    normal: triangle.normals.map(|normals| normals[i]),
+                    uv: triangle.uvs.map(|uvs| uvs[i]),
+                };
+                let index = *lookup.entry(vertex.key()).or_insert_with(|| {
+                    mesh.vertices.push(vertex);
+                    (mesh.vertices.len() - 1) as u32
+                });
+                mesh.indices.push(index);
+            }
+        }
+        mesh
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    pub fn triangle(&self, index: usize) -> Triangle {
+        let corners = [0, 1, 2].map(|i| self.vertices[self.indices[index * 3 + i] as usize]);
+        let mut triangle = Triangle::new(corners.map(|vertex| vertex.position));
+        // an attribute only carries over when all three corners have it
+        if let [Some(n1), Some(n2), Some(n3)] = corners.map(|vertex| vertex.normal) {
+            triangle.normals = Some([n1, n2, n3]);
+        }
+        if let [Some(t1), Some(t2), Some(t3)] = corners.map(|vertex| vertex.uv) {
+            triangle.uvs = Some([t1, t2, t3]);
+        }
+        triangle
+    }
+
+    pub fn to_triangles(&self) -> Vec<Triangle> {
+        (0..self.triangle_count()).map(|i| self.triangle(i)).collect()
+    }
+}
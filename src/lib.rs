@@ -0,0 +1,39 @@
+// a software rasterizer, everything from the math types up to image output is usable as a library,
+// the command line and the SDL2 window belong to the binary in main.rs
+
+// math
+pub mod matrix;
+pub mod quaternion;
+pub mod vector2;
+pub mod vector3;
+pub mod vector4;
+
+// geometry and assets
+pub mod triangle;
+pub mod indexed_mesh;
+pub mod mesh;
+pub mod obj;
+pub mod texture;
+pub mod assets;
+
+// scene description
+pub mod camera;
+pub mod controller;
+pub mod light;
+pub mod material;
+pub mod scene;
+pub mod scene_file;
+
+// rasterizer and output
+pub mod clipping;
+pub mod graphics;
+pub mod framebuffer;
+pub mod output;
+
+pub mod error;
+
+pub use error::{Error, Result};
+pub use scene::{Scene, render};
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,100 @@
+use crate::material::Material;
+use crate::vector3::Vector3;
+
+pub struct AmbientLight {
+    pub color: (u8, u8, u8),
+    pub intensity: f32,
+}
+
+// a light infinitely far away, direction is the way the light travels
+pub struct DirectionalLight {
+    pub direction: Vector3,
+    pub color: (u8, u8, u8),
+    pub intensity: f32,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Vector3, color: (u8, u8, u8), intensity: f32) -> Self {
+        Self { direction: direction.normalized(), color, intensity }
+    }
+}
+
+pub struct Lighting {
+    pub ambient: AmbientLight,
+    pub directional: Vec<DirectionalLight>,
+}
+
+impl Lighting {
+    pub fn new(ambient: AmbientLight, directional: Vec<DirectionalLight>) -> Self {
+        Self { ambient, directional }
+    }
+
+    // every surface is drawn in its plain material color
+    pub fn unlit() -> Self {
+        Self::new(AmbientLight { color: (255, 255, 255), intensity: 1.0 }, Vec::new())
+    }
+
+    // lambertian diffuse plus an optional blinn-phong highlight, to_eye points from the surface to the camera
+    pub fn sample(&self, normal: &Vector3, to_eye: &Vector3, material: &Material) -> LightSample {
+        let mut diffuse = scale_color(self.ambient.color, self.ambient.intensity);
+        let mut specular = Vector3::new(0.0, 0.0, 0.0);
+        for directional in &self.directional {
+            let lambert = -normal.dot(&directional.direction);
+            if lambert <= 0.0 {
+                continue;
+            }
+            diffuse += scale_color(directional.color, directional.intensity * lambert);
+            if material.specular > 0.0 {
+                let half_vector = (*to_eye - directional.direction).normalized();
+                let highlight = normal.dot(&half_vector).max(0.0).powf(material.shininess);
+                specular += scale_color(directional.color, directional.intensity * material.specular * highlight);
+            }
+        }
+        LightSample { diffuse, specular }
+    }
+
+    pub fn illuminate(&self, normal: &Vector3, to_eye: &Vector3, material: &Material) -> Vector3 {
+        self.sample(normal, to_eye, material).apply(&material.base_color())
+    }
+
+    pub fn shade(&self, normal: &Vector3, to_eye: &Vector3, material: &Material) -> (u8, u8, u8) {
+        to_rgb(&self.illuminate(normal, to_eye, material))
+    }
+}
+
+// the light arriving at a point, kept apart from the surface color so textures can be applied afterwards
+#[derive(Copy, Clone, Debug, Default)]
+pub struct LightSample {
+    pub diffuse: Vector3,
+    pub specular: Vector3,
+}
+
+impl LightSample {
+    // base color and result have channels from 0 to 255
+    pub fn apply(&self, base: &Vector3) -> Vector3 {
+        *base * self.diffuse + self.specular * 255.0
+    }
+}
+
+impl Default for Lighting {
+    // a soft fill plus a key light shining from over the camera's left shoulder
+    fn default() -> Self {
+        Self::new(
+            AmbientLight { color: (255, 255, 255), intensity: 0.25 },
+            vec![DirectionalLight::new(Vector3::new(1.0, -1.0, 1.0), (255, 255, 255), 0.85)],
+        )
+    }
+}
+
+// colors are handled as floats from 0 to 255 while lighting is computed
+pub fn to_rgb(color: &Vector3) -> (u8, u8, u8) {
+    (
+        color.x.clamp(0.0, 255.0) as u8,
+        color.y.clamp(0.0, 255.0) as u8,
+        color.z.clamp(0.0, 255.0) as u8,
+    )
+}
+
+fn scale_color(color: (u8, u8, u8), k: f32) -> Vector3 {
+    Vector3::new(color.0 as f32, color.1 as f32, color.2 as f32) * (k / 255.0)
+}
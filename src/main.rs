@@ -1,132 +1,168 @@
-use crate::camera::Camera;
-use crate::mesh::{Mesh, teapot};
-use crate::vector3::Vector3;
+mod cli;
+#[cfg(feature = "sdl")]
+mod viewer;
 
-use sdl2::EventPump;
-use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
-use sdl2::keyboard::Scancode;
-use sdl2::pixels::Color;
+#[cfg(test)]
+mod cli_test;
 
-use std::time::Duration;
+use cli::{Options, USAGE, parse_args};
+use nicks_software_renderer::assets::AssetPaths;
+use nicks_software_renderer::camera::{Camera, vertical_fov};
+use nicks_software_renderer::error::Result;
+use nicks_software_renderer::framebuffer::Framebuffer;
+use nicks_software_renderer::light::Lighting;
+use nicks_software_renderer::material::{Material, Shading};
+use nicks_software_renderer::mesh::{Mesh, teapot};
+use nicks_software_renderer::output::save_image;
+use nicks_software_renderer::quaternion::Quaternion;
+use nicks_software_renderer::scene::{Scene, render};
+use nicks_software_renderer::scene_file::load_scene;
+use nicks_software_renderer::vector3::Vector3;
 
-mod matrix;
-mod vector3;
-mod triangle;
-mod mesh;
-mod camera;
-mod graphics;
+use std::path::Path;
+use std::time::Instant;
 
-mod test;
-
-const WINDOW_WIDTH: u32 = 1920;
-const WINDOW_HEIGHT: u32 = 1080;
 const FOV: f32 = 100.0;
+const Z_NEAR: f32 = 1.0;
+const Z_FAR: f32 = 200.0;
 
-fn handle_input (event_pump: &EventPump, mesh: &mut Mesh) {
-    if event_pump.keyboard_state().is_scancode_pressed(Scancode::W) {
-        mesh.translate(0.0, 0.0, -0.1);
-    }
-    if event_pump.keyboard_state().is_scancode_pressed(Scancode::S) {
-        mesh.translate(0.0, 0.0, 0.1);
-    }
-    if event_pump.keyboard_state().is_scancode_pressed(Scancode::D) {
-        mesh.translate(-0.1, 0.0, 0.0);
-    }
-    if event_pump.keyboard_state().is_scancode_pressed(Scancode::A) {
-        mesh.translate(0.1, 0.0, 0.0);
+// models without normals of their own are smoothed up to this angle, in degrees
+const CREASE_ANGLE: f32 = 60.0;
+const MODEL_SPACING: f32 = 10.0;
+
+// models take turns being red, green and blue
+fn model_material(index: usize) -> Material {
+    let colors = [(255, 127, 127), (127, 255, 127), (127, 127, 255)];
+    Material {
+        shading: Shading::Phong,
+        specular: 0.5,
+        ..Material::new(colors[index % colors.len()])
     }
-    if event_pump.keyboard_state().is_scancode_pressed(Scancode::Space) {
-        mesh.translate(0.0, -0.1, 0.0);
+}
+
+const BACKGROUND: (u8, u8, u8) = (255, 255, 255);
+
+// load errors name the file they came from
+fn build_scene(options: &Options) -> std::result::Result<Scene, String> {
+    let assets = AssetPaths::standard();
+    let mut scene = match &options.scene {
+        Some(path) => assets.find(path)
+            .and_then(|path| load_scene(&path))
+            .map_err(|error| format!("failed to load {}: {}", path.display(), error))?,
+        None => {
+            let camera = Camera::new(
+                Vector3::new(0.0, 0.0, 0.0),
+                Quaternion::identity(),
+                FOV,
+                vertical_fov(FOV, options.aspect_ratio()),
+                Z_NEAR,
+                Z_FAR,
+            );
+            Scene::new(camera, Lighting::default(), BACKGROUND)
+        },
+    };
+
+    // without a scene or models the viewer shows three teapots
+    let mut models = Vec::new();
+    for path in &options.models {
+        let mut model = assets.load_model(path)
+            .map_err(|error| format!("failed to load {}: {}", path.display(), error))?;
+        if model.geometry.vertices.iter().any(|vertex| vertex.normal.is_none()) {
+            model.generate_normals(CREASE_ANGLE.to_radians());
+        }
+        models.push(model);
     }
-    if event_pump.keyboard_state().is_scancode_pressed(Scancode::LCtrl) {
-        mesh.translate(0.0, 0.1, 0.0);
+    if models.is_empty() && options.scene.is_none() {
+        models = (0..3).map(|_| teapot())
+            .collect::<Result<Vec<Mesh>>>()
+            .map_err(|error| format!("failed to load the bundled teapot: {}", error))?;
     }
-    if event_pump.keyboard_state().is_scancode_pressed(Scancode::Left) {
-        mesh.global_rotate(0.0, 0.025, 0.0);
+
+    // models given on the command line are lined up along x in front of the camera
+    let offset = (models.len() as f32 - 1.0) / 2.0;
+    for (index, mut model) in models.into_iter().enumerate() {
+        model.translate((index as f32 - offset) * MODEL_SPACING, 0.0, 10.0);
+        scene.add(model, model_material(index));
     }
-    if event_pump.keyboard_state().is_scancode_pressed(Scancode::Right) {
-        mesh.global_rotate(0.0, -0.025, 0.0);
+
+    if let Some(fov) = options.fov {
+        scene.camera.h_fov = fov;
+        scene.camera.v_fov = vertical_fov(fov, options.aspect_ratio());
     }
-    if event_pump.keyboard_state().is_scancode_pressed(Scancode::Up) {
-        mesh.global_rotate(-0.025, 0.0, 0.0);
+    if let Some(near) = options.near {
+        scene.camera.z_near = near;
     }
-    if event_pump.keyboard_state().is_scancode_pressed(Scancode::Down) {
-        mesh.global_rotate(0.025, 0.0, 0.0);
+    if let Some(far) = options.far {
+        scene.camera.z_far = far;
     }
-    if event_pump.keyboard_state().is_scancode_pressed(Scancode::Q) {
-        mesh.global_rotate(0.0, 0.0, -0.025);
+    // only one of the planes may have been given, the other comes from the scene or the defaults
+    if scene.camera.z_near >= scene.camera.z_far {
+        return Err(format!("--near ({}) must be closer than --far ({})", scene.camera.z_near, scene.camera.z_far));
     }
-    if event_pump.keyboard_state().is_scancode_pressed(Scancode::E) {
-        mesh.global_rotate(0.0, 0.0, 0.025);
+    if let Some(background) = options.background {
+        scene.background = background;
     }
+    Ok(scene)
 }
 
-fn control_fov (event_pump: &EventPump, camera: &mut Camera) {
-    if event_pump.keyboard_state().is_scancode_pressed(Scancode::O) { 
-        camera.h_fov -= 0.25;
-    }
-    if event_pump.keyboard_state().is_scancode_pressed(Scancode::P) { 
-        camera.h_fov += 0.25;
-    }
-    if event_pump.keyboard_state().is_scancode_pressed(Scancode::K) { 
-        camera.v_fov -= 0.25;
-    }
-    if event_pump.keyboard_state().is_scancode_pressed(Scancode::L) { 
-        camera.v_fov += 0.25;
-    }
+// renders without a window, when several frames are asked for the average frame time is reported
+fn render_headless(scene: &Scene, options: &Options, path: &Path) -> Result<()> {
+    let mut framebuffer = Framebuffer::new(options.width, options.height)?;
+    let frames = options.frames.unwrap_or(1);
+    let start = Instant::now();
+    for _ in 0..frames {
+        render(scene, &mut framebuffer);
+    }
+    if frames > 1 {
+        let milliseconds = start.elapsed().as_secs_f64() * 1000.0 / frames as f64;
+        eprintln!("rendered {} frames, {:.2} ms per frame", frames, milliseconds);
+    }
+    save_image(&framebuffer, path)
 }
 
 fn main() {
-    let sdl_context = sdl2::init().unwrap();
-    let video_subsystem = sdl_context.video().unwrap();
-    let window = video_subsystem.window("rust-sdl2 cube playground", WINDOW_WIDTH, WINDOW_HEIGHT)
-        .position_centered()
-        .build()
-        .unwrap();
-    let mut canvas = window.into_canvas().present_vsync().build().unwrap();
-    let mut event_pump = sdl_context.event_pump().unwrap();
-
-    let mut teapot1 = teapot();
-    teapot1.translate(-10.0, 0.0, 10.0);
-    let mut teapot2 = teapot();
-    teapot2.translate(0.0, 0.0, 10.0);
-    let mut teapot3 = teapot();
-    teapot3.translate(10.0, 0.0, 10.0);
-
-    let mut camera = Camera {
-        position: Vector3::new(0.0, 0.0, 0.0),
-        rotation: Vector3::new(0.0, 0.0, 0.0),
-        h_fov: FOV,
-        v_fov: (FOV * (WINDOW_WIDTH as f32 / WINDOW_HEIGHT as f32)) - FOV,
-        z_near: 1.0,
-        z_far: 200.0,
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+            std::process::exit(2);
+        },
     };
+    if options.help {
+        println!("{}", USAGE);
+        return;
+    }
 
-    'running: loop {
-        for event in event_pump.poll_iter() {
-            match event {
-                Event::Quit { .. } |
-                Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
-                    break 'running
-                },
-                _ => {}
-            }
-        }
-
-        handle_input(&event_pump, &mut teapot1);
-        handle_input(&event_pump, &mut teapot2);
-        handle_input(&event_pump, &mut teapot3);
-        control_fov(&event_pump, &mut camera);
+    let scene = match build_scene(&options) {
+        Ok(scene) => scene,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        },
+    };
 
-        canvas.set_draw_color(Color::RGB(255, 255, 255));
-        canvas.clear();
+    // an output path renders to disk instead of opening a window
+    if let Some(path) = &options.output {
+        if let Err(error) = render_headless(&scene, &options, path) {
+            eprintln!("failed to write {}: {}", path.display(), error);
+            std::process::exit(1);
+        }
+        return;
+    }
 
-        teapot1.draw(&mut canvas, &camera, (255, 127, 127));
-        teapot2.draw(&mut canvas, &camera, (127, 255, 127));
-        teapot3.draw(&mut canvas, &camera, (127, 127, 255));
+    run_viewer(scene, &options);
+}
 
-        canvas.present();
-        ::std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 60));
+#[cfg(feature = "sdl")]
+fn run_viewer(scene: Scene, options: &Options) {
+    if let Err(error) = viewer::run_window(scene, options.width, options.height, options.frames) {
+        eprintln!("{}", error);
+        std::process::exit(1);
     }
 }
+
+#[cfg(not(feature = "sdl"))]
+fn run_viewer(_scene: Scene, _options: &Options) {
+    eprintln!("this build has no window support, pass an output image with -o or rebuild with the sdl feature");
+    std::process::exit(2);
+}
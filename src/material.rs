@@ -0,0 +1,66 @@
+use crate::texture::{Filter, Texture};
+use crate::vector2::Vector2;
+use crate::vector3::Vector3;
+
+use std::sync::Arc;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Shading {
+    // one color per triangle from its face normal
+    Flat,
+    // lighting is evaluated at the vertices and the colors are interpolated
+    Gouraud,
+    // normals are interpolated and lighting is evaluated for every pixel
+    Phong,
+}
+
+#[derive(Clone, Debug)]
+pub struct Material {
+    pub color: (u8, u8, u8),
+    // draws the edges of every triangle in black on top of the shaded surface
+    pub outline: bool,
+    pub shading: Shading,
+    // strength and tightness of the blinn-phong highlight, no highlight when specular is 0
+    pub specular: f32,
+    pub shininess: f32,
+    // multiplied with color, only used on triangles that have texture coordinates
+    pub texture: Option<Arc<Texture>>,
+    pub filter: Filter,
+}
+
+impl Material {
+    pub fn new(color: (u8, u8, u8)) -> Self {
+        Self {
+            color,
+            outline: false,
+            shading: Shading::Flat,
+            specular: 0.0,
+            shininess: 32.0,
+            texture: None,
+            filter: Filter::Bilinear,
+        }
+    }
+
+    pub fn with_outline(color: (u8, u8, u8)) -> Self {
+        Self { outline: true, ..Self::new(color) }
+    }
+
+    pub fn with_texture(color: (u8, u8, u8), texture: Arc<Texture>) -> Self {
+        Self { texture: Some(texture), ..Self::new(color) }
+    }
+
+    pub fn base_color(&self) -> Vector3 {
+        Vector3::new(self.color.0 as f32, self.color.1 as f32, self.color.2 as f32)
+    }
+
+    // color of the surface before lighting, channels from 0 to 255
+    pub fn albedo(&self, uv: Option<Vector2>) -> Vector3 {
+        let base = self.base_color();
+        match (&self.texture, uv) {
+            (Some(texture), Some(uv)) => {
+                base * texture.sample(uv.x, uv.y, self.filter) / 255.0
+            },
+            _ => base,
+        }
+    }
+}
@@ -1,5 +1,12 @@
+#![allow(clippy::needless_range_loop)]
+
+use crate::quaternion::Quaternion;
 use crate::vector3::Vector3;
+use crate::vector4::Vector4;
+
+use std::ops::{Add, Mul, Sub};
 
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Matrix4x4 {
     pub m: [[f32; 4]; 4],
 }
@@ -8,6 +15,206 @@ impl Matrix4x4 {
     pub fn new(m: [[f32; 4]; 4]) -> Self {
         Self { m }
     }
+
+    pub fn identity() -> Self {
+        identity_matrix()
+    }
+
+    // object to parent transform, scale then rotate then translate
+    pub fn from_translation_rotation_scale(translation: &Vector3, rotation: &Quaternion, scale: &Vector3) -> Self {
+        translation_matrix(translation.x, translation.y, translation.z)
+            * (rotation.to_matrix() * scale_matrix(scale.x, scale.y, scale.z))
+    }
+
+    // projections map view space, looking down +z, to clip space with depth from 0 on the near plane to 1 on the far one
+
+    // symmetric perspective from a vertical field of view in degrees and a width / height aspect ratio
+    pub fn perspective(v_fov: f32, aspect: f32, z_near: f32, z_far: f32) -> Self {
+        let vf = 1.0 / (v_fov.to_radians() / 2.0).tan();
+        let lambda = z_far / (z_far - z_near);
+        //  vf/aspect   0       0                 0
+        //          0  vf       0                 0
+        //          0   0  lambda  -z_near * lambda
+        //          0   0       1                 0
+        Self { m: [
+            [vf / aspect, 0.0, 0.0, 0.0],
+            [0.0, vf, 0.0, 0.0],
+            [0.0, 0.0, lambda, -z_near * lambda],
+            [0.0, 0.0, 1.0, 0.0],
+        ]}
+    }
+
+    // off-axis perspective, the edges of the view are given where they cross the near plane,
+    // e.g. for one eye of a stereo pair or one tile of a larger image
+    pub fn frustum(left: f32, right: f32, bottom: f32, top: f32, z_near: f32, z_far: f32) -> Self {
+        let lambda = z_far / (z_far - z_near);
+        Self { m: [
+            [2.0 * z_near / (right - left), 0.0, -(right + left) / (right - left), 0.0],
+            [0.0, 2.0 * z_near / (top - bottom), -(top + bottom) / (top - bottom), 0.0],
+            [0.0, 0.0, lambda, -z_near * lambda],
+            [0.0, 0.0, 1.0, 0.0],
+        ]}
+    }
+
+    // parallel projection of the box between the planes, w stays 1
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, z_near: f32, z_far: f32) -> Self {
+        Self { m: [
+            [2.0 / (right - left), 0.0, 0.0, -(right + left) / (right - left)],
+            [0.0, 2.0 / (top - bottom), 0.0, -(top + bottom) / (top - bottom)],
+            [0.0, 0.0, 1.0 / (z_far - z_near), -z_near / (z_far - z_near)],
+            [0.0, 0.0, 0.0, 1.0],
+        ]}
+    }
+
+    // turns a projection into its reversed-z version with 1 on the near plane and 0 on the far one,
+    // floats are densest near 0 so distant surfaces fight less, the depth test has to flip with it
+    pub fn reverse_depth(&self) -> Self {
+        let mut m = self.m;
+        for col in 0..4 {
+            m[2][col] = self.m[3][col] - self.m[2][col];
+        }
+        Self { m }
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut m = [[0.0; 4]; 4];
+        for row in 0..4 {
+            for col in 0..4 {
+                m[row][col] = self.m[col][row];
+            }
+        }
+        Self { m }
+    }
+
+    pub fn determinant(&self) -> f32 {
+        let (s, c) = self.minors();
+        determinant_from_minors(&s, &c)
+    }
+
+    // none when the matrix is singular, e.g. a scale of zero along some axis
+    pub fn inverse(&self) -> Option<Self> {
+        let (s, c) = self.minors();
+        let determinant = determinant_from_minors(&s, &c);
+        if determinant == 0.0 || !determinant.is_finite() {
+            return None;
+        }
+        let a = &self.m;
+        let adjugate = [
+            [
+                a[1][1] * c[5] - a[1][2] * c[4] + a[1][3] * c[3],
+                -a[0][1] * c[5] + a[0][2] * c[4] - a[0][3] * c[3],
+                a[3][1] * s[5] - a[3][2] * s[4] + a[3][3] * s[3],
+                -a[2][1] * s[5] + a[2][2] * s[4] - a[2][3] * s[3],
+            ],
+            [
+                -a[1][0] * c[5] + a[1][2] * c[2] - a[1][3] * c[1],
+                a[0][0] * c[5] - a[0][2] * c[2] + a[0][3] * c[1],
+                -a[3][0] * s[5] + a[3][2] * s[2] - a[3][3] * s[1],
+                a[2][0] * s[5] - a[2][2] * s[2] + a[2][3] * s[1],
+            ],
+            [
+                a[1][0] * c[4] - a[1][1] * c[2] + a[1][3] * c[0],
+                -a[0][0] * c[4] + a[0][1] * c[2] - a[0][3] * c[0],
+                a[3][0] * s[4] - a[3][1] * s[2] + a[3][3] * s[0],
+                -a[2][0] * s[4] + a[2][1] * s[2] - a[2][3] * s[0],
+            ],
+            [
+                -a[1][0] * c[3] + a[1][1] * c[1] - a[1][2] * c[0],
+                a[0][0] * c[3] - a[0][1] * c[1] + a[0][2] * c[0],
+                -a[3][0] * s[3] + a[3][1] * s[1] - a[3][2] * s[0],
+                a[2][0] * s[3] - a[2][1] * s[1] + a[2][2] * s[0],
+            ],
+        ];
+        Some(Self { m: adjugate } * (1.0 / determinant))
+    }
+
+    // transforms normals the same way this matrix transforms surfaces, for plain rotations that is the matrix itself
+    pub fn inverse_transpose(&self) -> Option<Self> {
+        self.inverse().map(|inverse| inverse.transpose())
+    }
+
+    // 2x2 determinants of the top two rows and of the bottom two rows, shared by determinant and inverse
+    fn minors(&self) -> ([f32; 6], [f32; 6]) {
+        let a = &self.m;
+        let top = [
+            a[0][0] * a[1][1] - a[1][0] * a[0][1],
+            a[0][0] * a[1][2] - a[1][0] * a[0][2],
+            a[0][0] * a[1][3] - a[1][0] * a[0][3],
+            a[0][1] * a[1][2] - a[1][1] * a[0][2],
+            a[0][1] * a[1][3] - a[1][1] * a[0][3],
+            a[0][2] * a[1][3] - a[1][2] * a[0][3],
+        ];
+        let bottom = [
+            a[2][0] * a[3][1] - a[3][0] * a[2][1],
+            a[2][0] * a[3][2] - a[3][0] * a[2][2],
+            a[2][0] * a[3][3] - a[3][0] * a[2][3],
+            a[2][1] * a[3][2] - a[3][1] * a[2][2],
+            a[2][1] * a[3][3] - a[3][1] * a[2][3],
+            a[2][2] * a[3][3] - a[3][2] * a[2][3],
+        ];
+        (top, bottom)
+    }
+
+    // for affine transforms, w stays 1 so no divide is needed
+    pub fn transform_point(&self, point: &Vector3) -> Vector3 {
+        (*self * Vector4::from_point(point)).xyz()
+    }
+
+    // ignores the translation column
+    pub fn transform_direction(&self, direction: &Vector3) -> Vector3 {
+        (*self * Vector4::from_direction(direction)).xyz()
+    }
+
+    // for projections, the result is divided by w
+    pub fn project_point(&self, point: &Vector3) -> Vector3 {
+        (*self * Vector4::from_point(point)).perspective_divide()
+    }
+}
+
+// laplace expansion along the top two rows, each top minor pairs with the bottom minor of the other two columns
+fn determinant_from_minors(s: &[f32; 6], c: &[f32; 6]) -> f32 {
+    s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0]
+}
+
+impl Mul<Vector4> for Matrix4x4 {
+    type Output = Vector4;
+
+    fn mul(self, v: Vector4) -> Vector4 {
+        let row = |row: usize| self.m[row][0] * v.x + self.m[row][1] * v.y + self.m[row][2] * v.z + self.m[row][3] * v.w;
+        Vector4::new(row(0), row(1), row(2), row(3))
+    }
+}
+
+impl Mul for Matrix4x4 {
+    type Output = Matrix4x4;
+
+    fn mul(self, other: Matrix4x4) -> Matrix4x4 {
+        matrix_matrix_multiply(&self, &other)
+    }
+}
+
+impl Mul<f32> for Matrix4x4 {
+    type Output = Matrix4x4;
+
+    fn mul(self, k: f32) -> Matrix4x4 {
+        matrix_scale(&self, k)
+    }
+}
+
+impl Add for Matrix4x4 {
+    type Output = Matrix4x4;
+
+    fn add(self, other: Matrix4x4) -> Matrix4x4 {
+        matrix_add(&self, &other)
+    }
+}
+
+impl Sub for Matrix4x4 {
+    type Output = Matrix4x4;
+
+    fn sub(self, other: Matrix4x4) -> Matrix4x4 {
+        matrix_subtract(&self, &other)
+    }
 }
 
 pub fn matrix_scale(m1: &Matrix4x4, k: f32) -> Matrix4x4 {
@@ -111,9 +318,13 @@ pub fn scale_matrix(x: f32, y: f32, z: f32) -> Matrix4x4 {
 
 pub fn translation_matrix(x: f32, y: f32, z: f32) -> Matrix4x4 {
     Matrix4x4 { m: [
-        [0.0, 0.0, 0.0, x],
-        [0.0, 0.0, 0.0, y],
-        [0.0, 0.0, 0.0, z],
+        [1.0, 0.0, 0.0, x],
+        [0.0, 1.0, 0.0, y],
+        [0.0, 0.0, 1.0, z],
         [0.0, 0.0, 0.0, 1.0],
     ]}
-}
\ No newline at end of file
+}
+// rotates around x, then y, then z
+pub fn euler_rotation_matrix(rotation: &Vector3) -> Matrix4x4 {
+    z_rotation_matrix(rotation.z) * (y_rotation_matrix(rotation.y) * x_rotation_matrix(rotation.x))
+}
@@ -1,81 +1,128 @@
-use std::io::BufRead;
-use std::io::BufReader;
-use std::fs::File;
-use crate::matrix::{matrix_vector_multiply, x_rotation_matrix, y_rotation_matrix, z_rotation_matrix};
-use crate::vector3::{Vector3, add, subtract};
-use crate::triangle::Triangle;
+use std::fs;
+use std::path::Path;
+use std::collections::HashMap;
+use crate::matrix::Matrix4x4;
+use crate::quaternion::{EulerOrder, Quaternion};
+use crate::vector2::Vector2;
+use crate::vector3::Vector3;
+use crate::error::{Error, Result};
+use crate::indexed_mesh::IndexedMesh;
+use crate::triangle::{Triangle, calculate_area_normal, calculate_normal};
 use crate::camera::Camera;
 use crate::graphics::project;
+use crate::framebuffer::Framebuffer;
+use crate::light::Lighting;
+use crate::material::Material;
 
-use sdl2::render::Canvas;
-
-use std::fs;
-
+// geometry is kept in object space, the transform is only applied when the mesh is drawn
 #[derive(Debug)]
 pub struct Mesh {
     pub position: Vector3,
-    pub rotation: Vector3,
-    pub triangles: Vec<Triangle>,
+    pub rotation: Quaternion,
+    pub scale: Vector3,
+    pub geometry: IndexedMesh,
 }
 
 impl Mesh {
-    pub fn new(position: Vector3, rotation: Vector3, triangles: Vec<Triangle>) -> Self {
-        Self { position, rotation, triangles }
+    pub fn new(position: Vector3, rotation: Quaternion, triangles: Vec<Triangle>) -> Self {
+        Self::with_geometry(position, rotation, IndexedMesh::from_triangles(&triangles))
+    }
+
+    pub fn with_geometry(position: Vector3, rotation: Quaternion, geometry: IndexedMesh) -> Self {
+        Self { position, rotation, scale: Vector3::new(1.0, 1.0, 1.0), geometry }
+    }
+
+    // object space triangle soup
+    pub fn triangles(&self) -> Vec<Triangle> {
+        self.geometry.to_triangles()
     }
 
     pub fn translate(&mut self, x: f32, y: f32, z: f32) {
         self.position.translate(x, y, z);
-        for triangle in &mut self.triangles {
-            for vertex in &mut triangle.vertices {
-                vertex.translate(x, y, z);
-            }
-        }
     }
 
+    // rotates around the mesh's own position, by x then y then z around the world axes
     pub fn rotate(&mut self, x: f32, y: f32, z: f32) {
-        self.rotation.translate(x, y, z);
-        let x_rot_matrix = x_rotation_matrix(x);
-        let y_rot_matrix = y_rotation_matrix(y);
-        let z_rot_matrix = z_rotation_matrix(z);
-        for triangle in &mut self.triangles {
-            for vertex in &mut triangle.vertices {
-                *vertex = subtract(vertex, &self.position);
+        self.rotate_by(&Quaternion::from_euler(&Vector3::new(x, y, z), EulerOrder::Xyz));
+    }
 
-                let x_mult = matrix_vector_multiply(&x_rot_matrix, vertex);
-                *vertex = Vector3::new(x_mult[0][0], x_mult[1][0], x_mult[2][0]);
+    pub fn rotate_by(&mut self, rotation: &Quaternion) {
+        self.rotation = (*rotation * self.rotation).normalized();
+    }
+
+    // rotates around the world origin, which also moves the mesh
+    pub fn global_rotate(&mut self, x: f32, y: f32, z: f32) {
+        let delta = Quaternion::from_euler(&Vector3::new(x, y, z), EulerOrder::Xyz);
+        self.position = delta.rotate(&self.position);
+        self.rotate_by(&delta);
+    }
 
-                let y_mult = matrix_vector_multiply(&y_rot_matrix, vertex);
-                *vertex = Vector3::new(y_mult[0][0], y_mult[1][0], y_mult[2][0]);
+    pub fn non_uniform_scale(&mut self, x: f32, y: f32, z: f32) {
+        self.scale.non_uniform_scale(x, y, z);
+    }
 
-                let z_mult = matrix_vector_multiply(&z_rot_matrix, vertex);
-                *vertex = Vector3::new(z_mult[0][0], z_mult[1][0], z_mult[2][0]);
+    // object to world transform, scale then rotate then translate
+    pub fn model_matrix(&self) -> Matrix4x4 {
+        Matrix4x4::from_translation_rotation_scale(&self.position, &self.rotation, &self.scale)
+    }
 
-                *vertex = add(vertex, &self.position);
+    pub fn normal_matrix(&self) -> Matrix4x4 {
+        normal_matrix(&self.model_matrix())
+    }
+
+    pub fn world_triangles(&self) -> Vec<Triangle> {
+        let model_matrix = self.model_matrix();
+        let normal_matrix = self.normal_matrix();
+        self.triangles().into_iter().map(|triangle| {
+            let mut world = triangle;
+            for vertex in &mut world.vertices {
+                *vertex = transform_point(&model_matrix, vertex);
             }
-        }
+            if let Some(normals) = &mut world.normals {
+                for normal in normals {
+                    *normal = transform_normal(&normal_matrix, normal);
+                }
+            }
+            world
+        }).collect()
     }
 
-    pub fn global_rotate(&mut self, x: f32, y: f32, z: f32) {
-        self.rotation.translate(x, y, z);
-        let x_rot_matrix = x_rotation_matrix(x);
-        let y_rot_matrix = y_rotation_matrix(y);
-        let z_rot_matrix = z_rotation_matrix(z);
-        for triangle in &mut self.triangles {
-            for vertex in &mut triangle.vertices {
-                let x_mult = matrix_vector_multiply(&x_rot_matrix, vertex);
-                *vertex = Vector3::new(x_mult[0][0], x_mult[1][0], x_mult[2][0]);
-
-                let y_mult = matrix_vector_multiply(&y_rot_matrix, vertex);
-                *vertex = Vector3::new(y_mult[0][0], y_mult[1][0], y_mult[2][0]);
-
-                let z_mult = matrix_vector_multiply(&z_rot_matrix, vertex);
-                *vertex = Vector3::new(z_mult[0][0], z_mult[1][0], z_mult[2][0]);
+    // averages the normals of the faces around each vertex, faces that meet at a sharper angle
+    // than crease_angle (in radians) keep a hard edge between them
+    pub fn generate_normals(&mut self, crease_angle: f32) {
+        let mut triangles = self.triangles();
+        let face_normals: Vec<Vector3> = triangles.iter().map(calculate_normal).collect();
+        let area_normals: Vec<Vector3> = triangles.iter().map(calculate_area_normal).collect();
+
+        let mut faces_at_position: HashMap<[u32; 3], Vec<usize>> = HashMap::new();
+        for (face, triangle) in triangles.iter().enumerate() {
+            for vertex in &triangle.vertices {
+                faces_at_position.entry(position_key(vertex)).or_default().push(face);
             }
         }
+
+        let threshold = crease_angle.cos();
+        for (face, triangle) in triangles.iter_mut().enumerate() {
+            let mut normals = [face_normals[face]; 3];
+            for (normal, vertex) in normals.iter_mut().zip(&triangle.vertices) {
+                let mut sum = Vector3::new(0.0, 0.0, 0.0);
+                for &other in &faces_at_position[&position_key(vertex)] {
+                    if face_normals[face].dot(&face_normals[other]) >= threshold {
+                        sum += area_normals[other];
+                    }
+                }
+                if sum.length_squared() > 0.0 {
+                    *normal = sum.normalized();
+                }
+            }
+            triangle.normals = Some(normals);
+        }
+        // vertices on a crease now have a different normal on each side and are split apart
+        self.geometry = IndexedMesh::from_triangles(&triangles);
     }
 
-    pub fn draw <T: sdl2::render::RenderTarget> (&self, canvas: &mut Canvas<T>, camera: &Camera, color: (u8, u8, u8)) {
-        project(canvas, camera, self, color);
+    pub fn draw (&self, framebuffer: &mut Framebuffer, camera: &Camera, material: &Material, lighting: &Lighting) {
+        project(framebuffer, camera, self, material, lighting);
     }
 }
 
@@ -90,60 +137,117 @@ pub fn cube (size: f32) -> Mesh {
     let v7 = Vector3::new(-half,  half,  half);
     let v8 = Vector3::new( half,  half,  half);
 
-    Mesh {
-        position: Vector3::new(0.0, 0.0, 0.0),
-        rotation: Vector3::new(0.0, 0.0, 0.0),
-        triangles: vec![
-                // neg z
-                Triangle::new([v1, v3, v4]),
-                Triangle::new([v1, v4, v2]),
-                // pos z
-                Triangle::new([v6, v8, v7]),
-                Triangle::new([v6, v7, v5]),
-                // neg x
-                Triangle::new([v5, v7, v3]),
-                Triangle::new([v5, v3, v1]),
-                // pos x
-                Triangle::new([v2, v4, v8]),
-                Triangle::new([v2, v8, v6]),
-                // neg y
-                Triangle::new([v5, v1, v2]),
-                Triangle::new([v5, v2, v6]),
-                // pos y
-                Triangle::new([v3, v7, v8]),
-                Triangle::new([v3, v8, v4]),
-    ]}
+    let x = Vector3::new(1.0, 0.0, 0.0);
+    let y = Vector3::new(0.0, 1.0, 0.0);
+    let z = Vector3::new(0.0, 0.0, 1.0);
+    let neg_x = Vector3::new(-1.0, 0.0, 0.0);
+    let neg_z = Vector3::new(0.0, 0.0, -1.0);
+
+    // each face shows the whole texture upright when looked at from outside the cube
+    let face = |vertices: [Vector3; 3], right: &Vector3, up: &Vector3| {
+        Triangle::with_uvs(vertices, vertices.map(|v| Vector2::new(
+            v.dot(right) / size + 0.5,
+            v.dot(up) / size + 0.5,
+        )))
+    };
+
+    Mesh::new(
+        Vector3::new(0.0, 0.0, 0.0),
+        Quaternion::identity(),
+        vec![
+            // neg z
+            face([v1, v3, v4], &x, &y),
+            face([v1, v4, v2], &x, &y),
+            // pos z
+            face([v6, v8, v7], &neg_x, &y),
+            face([v6, v7, v5], &neg_x, &y),
+            // neg x
+            face([v5, v7, v3], &neg_z, &y),
+            face([v5, v3, v1], &neg_z, &y),
+            // pos x
+            face([v2, v4, v8], &z, &y),
+            face([v2, v8, v6], &z, &y),
+            // neg y
+            face([v5, v1, v2], &x, &neg_z),
+            face([v5, v2, v6], &x, &neg_z),
+            // pos y
+            face([v3, v7, v8], &x, &z),
+            face([v3, v8, v4], &x, &z),
+        ],
+    )
 }
 
-pub fn teapot () -> Mesh {
-    let file_path = "/home/nick/Documents/Programming/rust/nicks-software-renderer/src/teapot_bezier.tris";
-    let file = File::open(file_path).unwrap();
-    let reader = BufReader::new(file);
+// the bezier patches meet at sharp seams around the spout, handle and lid
+const TEAPOT_CREASE_ANGLE: f32 = 60.0;
 
-    let mut triangles: Vec<Triangle> = Vec::new();
-    let mut triangle = Triangle { vertices: [
-        Vector3::new(0.0, 0.0, 0.0),
-        Vector3::new(0.0, 0.0, 0.0),
+// the bundled Utah teapot is compiled into the binary so it works from any directory
+const TEAPOT: &str = include_str!("teapot_bezier.tris");
+
+pub fn teapot () -> Result<Mesh> {
+    let mut mesh = Mesh::new(
         Vector3::new(0.0, 0.0, 0.0),
-    ] };
-
-    for (index, line) in reader.lines().enumerate() {
-        let line = line.unwrap();
-        let position: Vec<&str> = line.split(' ').collect();
-        let remainder = (index + 1) % 4;
-        if remainder != 0 {
-            let x = position[0].parse::<f32>().unwrap();
-            let y = position[1].parse::<f32>().unwrap();
-            let z = position[2].parse::<f32>().unwrap();
-            triangle.vertices[remainder - 1] = Vector3 { x, y, z };
-        } else {
-            triangles.push(triangle);
+        Quaternion::identity(),
+        parse_tris(TEAPOT)?,
+    );
+    mesh.generate_normals(TEAPOT_CREASE_ANGLE.to_radians());
+    Ok(mesh)
+}
+
+pub fn load_tris(path: &Path) -> Result<Mesh> {
+    let triangles = parse_tris(&fs::read_to_string(path)?)?;
+    Ok(Mesh::new(Vector3::new(0.0, 0.0, 0.0), Quaternion::identity(), triangles))
+}
+
+// a .tris file lists every triangle as three "x y z" lines, blank lines between triangles are ignored
+pub fn parse_tris(text: &str) -> Result<Vec<Triangle>> {
+    let mut triangles = Vec::new();
+    let mut vertices = Vec::with_capacity(3);
+    let mut last_line = 0;
+
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let coordinates: Vec<&str> = line.split_whitespace().collect();
+        if coordinates.is_empty() {
+            continue;
         }
+        if coordinates.len() != 3 {
+            return Err(Error::parse(line_number, format!("expected \"x y z\" but found \"{}\"", line.trim())));
+        }
+        let mut position = [0.0; 3];
+        for (value, coordinate) in position.iter_mut().zip(&coordinates) {
+            *value = coordinate.parse::<f32>()
+                .map_err(|_| Error::parse(line_number, format!("expected a number but found \"{}\"", coordinate)))?;
+        }
+        vertices.push(Vector3::new(position[0], position[1], position[2]));
+        if vertices.len() == 3 {
+            triangles.push(Triangle::new([vertices[0], vertices[1], vertices[2]]));
+            vertices.clear();
+        }
+        last_line = line_number;
     }
 
-    Mesh {
-        position: Vector3::new(0.0, 0.0, 0.0), 
-        rotation: Vector3::new(0.0, 0.0, 0.0),
-        triangles,
+    if !vertices.is_empty() {
+        return Err(Error::parse(last_line, "the file ends in the middle of a triangle"));
     }
+    Ok(triangles)
+}
+
+pub fn transform_point(m: &Matrix4x4, v: &Vector3) -> Vector3 {
+    m.transform_point(v)
+}
+
+pub fn transform_normal(normal_matrix: &Matrix4x4, n: &Vector3) -> Vector3 {
+    normal_matrix.transform_direction(n).normalized()
+}
+
+// normals need the inverse transpose of the model matrix to stay perpendicular under non uniform scale,
+// a flattened mesh has none and keeps its normals as they are
+pub fn normal_matrix(model_matrix: &Matrix4x4) -> Matrix4x4 {
+    model_matrix.inverse_transpose().unwrap_or_else(Matrix4x4::identity)
+}
+
+// vertices are shared by exact position, the files we load repeat them bit for bit
+fn position_key(v: &Vector3) -> [u32; 3] {
+    // treat -0.0 and 0.0 as the same position
+    [(v.x + 0.0).to_bits(), (v.y + 0.0).to_bits(), (v.z + 0.0).to_bits()]
 }
\ No newline at end of file
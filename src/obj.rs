@@ -0,0 +1,156 @@
+// https://paulbourke.net/dataformats/obj/
+use crate::error::{Error, Result};
+use crate::mesh::Mesh;
+use crate::quaternion::Quaternion;
+use crate::triangle::Triangle;
+use crate::vector2::Vector2;
+use crate::vector3::Vector3;
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+pub struct ObjGroup {
+    pub name: String,
+    pub triangles: Vec<Triangle>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct FaceVertex {
+    position: usize,
+    uv: Option<usize>,
+    normal: Option<usize>,
+}
+
+// loads every group of the file into a single mesh
+pub fn load_obj(path: &Path) -> Result<Mesh> {
+    let triangles = load_obj_groups(path)?
+        .into_iter()
+        .flat_map(|group| group.triangles)
+        .collect();
+    Ok(Mesh::new(Vector3::new(0.0, 0.0, 0.0), Quaternion::identity(), triangles))
+}
+
+pub fn load_obj_groups(path: &Path) -> Result<Vec<ObjGroup>> {
+    parse_obj(BufReader::new(File::open(path)?))
+}
+
+pub fn parse_obj<R: BufRead> (reader: R) -> Result<Vec<ObjGroup>> {
+    let mut positions: Vec<Vector3> = Vec::new();
+    let mut uvs: Vec<Vector2> = Vec::new();
+    let mut normals: Vec<Vector3> = Vec::new();
+    let mut groups = vec![ObjGroup { name: String::from("default"), triangles: Vec::new() }];
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line_number = index + 1;
+        let line = line.split('#').next().unwrap_or("");
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let arguments: Vec<&str> = tokens.collect();
+
+        match keyword {
+            "v" => {
+                let [x, y, z] = parse_floats::<3>(&arguments, line_number, "v")?;
+                positions.push(Vector3::new(x, y, z));
+            },
+            "vt" => {
+                let [u] = parse_floats::<1>(&arguments, line_number, "vt")?;
+                let v = match arguments.get(1) {
+                    Some(v) => parse_float(v, line_number)?,
+                    None => 0.0,
+                };
+                uvs.push(Vector2::new(u, v));
+            },
+            "vn" => {
+                let [x, y, z] = parse_floats::<3>(&arguments, line_number, "vn")?;
+                normals.push(Vector3::new(x, y, z).normalized());
+            },
+            "f" => {
+                if arguments.len() < 3 {
+                    return Err(Error::parse(line_number, "a face needs at least three vertices"));
+                }
+                let mut face = Vec::with_capacity(arguments.len());
+                for argument in &arguments {
+                    face.push(parse_face_vertex(argument, positions.len(), uvs.len(), normals.len(), line_number)?);
+                }
+                // polygons are assumed to be convex and are split into a triangle fan
+                let triangles = &mut groups.last_mut().unwrap().triangles;
+                for i in 1..face.len() - 1 {
+                    let corners = [face[0], face[i], face[i + 1]];
+                    let vertices = corners.map(|corner| positions[corner.position]);
+                    // normals and texture coordinates are only used when all three corners have one
+                    let mut triangle = Triangle::new(vertices);
+                    if let (Some(n1), Some(n2), Some(n3)) = (corners[0].normal, corners[1].normal, corners[2].normal) {
+                        triangle.normals = Some([normals[n1], normals[n2], normals[n3]]);
+                    }
+                    if let (Some(t1), Some(t2), Some(t3)) = (corners[0].uv, corners[1].uv, corners[2].uv) {
+                        triangle.uvs = Some([uvs[t1], uvs[t2], uvs[t3]]);
+                    }
+                    triangles.push(triangle);
+                }
+            },
+            "o" | "g" => {
+                let name = if arguments.is_empty() { String::from("default") } else { arguments.join(" ") };
+                if groups.last().unwrap().triangles.is_empty() {
+                    groups.last_mut().unwrap().name = name;
+                } else {
+                    groups.push(ObjGroup { name, triangles: Vec::new() });
+                }
+            },
+            // materials, smoothing groups, lines and points are not used by the renderer
+            _ => {},
+        }
+    }
+
+    groups.retain(|group| !group.triangles.is_empty());
+    Ok(groups)
+}
+
+fn parse_float(token: &str, line_number: usize) -> Result<f32> {
+    token.parse::<f32>()
+        .map_err(|_| Error::parse(line_number, format!("expected a number but found \"{}\"", token)))
+}
+
+fn parse_floats<const N: usize> (arguments: &[&str], line_number: usize, keyword: &str) -> Result<[f32; N]> {
+    if arguments.len() < N {
+        return Err(Error::parse(line_number, format!("\"{}\" needs {} values", keyword, N)));
+    }
+    let mut values = [0.0; N];
+    for (value, argument) in values.iter_mut().zip(arguments) {
+        *value = parse_float(argument, line_number)?;
+    }
+    Ok(values)
+}
+
+// face vertices look like "v", "v/vt", "v//vn" or "v/vt/vn"
+fn parse_face_vertex(token: &str, positions: usize, uvs: usize, normals: usize, line_number: usize) -> Result<FaceVertex> {
+    let mut parts = token.split('/');
+    let position = match parts.next() {
+        Some(part) if !part.is_empty() => resolve_index(part, positions, line_number)?,
+        _ => return Err(Error::parse(line_number, format!("face vertex \"{}\" has no position", token))),
+    };
+    let uv = match parts.next() {
+        Some(part) if !part.is_empty() => Some(resolve_index(part, uvs, line_number)?),
+        _ => None,
+    };
+    let normal = match parts.next() {
+        Some(part) if !part.is_empty() => Some(resolve_index(part, normals, line_number)?),
+        _ => None,
+    };
+    Ok(FaceVertex { position, uv, normal })
+}
+
+// indices start at 1, negative indices count back from the most recently defined element
+fn resolve_index(token: &str, count: usize, line_number: usize) -> Result<usize> {
+    let index = token.parse::<i64>()
+        .map_err(|_| Error::parse(line_number, format!("expected an index but found \"{}\"", token)))?;
+    let resolved = if index < 0 { count as i64 + index } else { index - 1 };
+    if index == 0 || resolved < 0 || resolved >= count as i64 {
+        return Err(Error::parse(line_number, format!("index {} is out of range", index)));
+    }
+    Ok(resolved as usize)
+}
@@ -0,0 +1,152 @@
+use crate::error::{Error, Result};
+use crate::framebuffer::Framebuffer;
+use crate::scene::{Scene, render};
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+// renders without a window and writes the frame to disk, the format is picked from the file extension
+pub fn render_to_file(scene: &Scene, width: u32, height: u32, path: &Path) -> Result<()> {
+    let mut framebuffer = Framebuffer::new(width, height)?;
+    render(scene, &mut framebuffer);
+    save_image(&framebuffer, path)
+}
+
+pub fn save_image(framebuffer: &Framebuffer, path: &Path) -> Result<()> {
+    let extension = path.extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase());
+    // the format is checked first so a bad extension doesn't leave an empty file behind
+    let write = match extension.as_deref() {
+        Some("png") => write_png::<BufWriter<File>>,
+        Some("ppm") => write_ppm::<BufWriter<File>>,
+        Some("bmp") => write_bmp::<BufWriter<File>>,
+        _ => return Err(Error::format(format!("unsupported image format for {}, expected .png, .ppm or .bmp", path.display()))),
+    };
+    let mut writer = BufWriter::new(File::create(path)?);
+    write(framebuffer, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+// http://netpbm.sourceforge.net/doc/ppm.html
+pub fn write_ppm<W: Write> (framebuffer: &Framebuffer, writer: &mut W) -> Result<()> {
+    write!(writer, "P6\n{} {}\n255\n", framebuffer.width, framebuffer.height)?;
+    writer.write_all(&framebuffer.color)?;
+    Ok(())
+}
+
+// 24 bit uncompressed BMP with a BITMAPINFOHEADER, rows are stored bottom up in BGR order
+pub fn write_bmp<W: Write> (framebuffer: &Framebuffer, writer: &mut W) -> Result<()> {
+    let row_size = (framebuffer.pitch() + 3) & !3;
+    let image_size = row_size * framebuffer.height as usize;
+    let header_size = 14 + 40;
+    // sizes are stored in 32 bits, when the whole file fits so do the image size, width and height
+    let file_size = u32::try_from(header_size + image_size)
+        .map_err(|_| Error::format(format!("a {}x{} image is too large for a BMP file", framebuffer.width, framebuffer.height)))?;
+
+    writer.write_all(b"BM")?;
+    writer.write_all(&file_size.to_le_bytes())?;
+    writer.write_all(&[0; 4])?;
+    writer.write_all(&(header_size as u32).to_le_bytes())?;
+
+    writer.write_all(&40u32.to_le_bytes())?;
+    writer.write_all(&(framebuffer.width as i32).to_le_bytes())?;
+    writer.write_all(&(framebuffer.height as i32).to_le_bytes())?;
+    writer.write_all(&1u16.to_le_bytes())?;
+    writer.write_all(&24u16.to_le_bytes())?;
+    writer.write_all(&0u32.to_le_bytes())?;
+    writer.write_all(&(image_size as u32).to_le_bytes())?;
+    writer.write_all(&2835i32.to_le_bytes())?;
+    writer.write_all(&2835i32.to_le_bytes())?;
+    writer.write_all(&[0; 8])?;
+
+    let mut row = vec![0; row_size];
+    for y in (0..framebuffer.height as usize).rev() {
+        let pixels = &framebuffer.color[y * framebuffer.pitch()..(y + 1) * framebuffer.pitch()];
+        for (bgr, rgb) in row.chunks_exact_mut(3).zip(pixels.chunks_exact(3)) {
+            bgr.copy_from_slice(&[rgb[2], rgb[1], rgb[0]]);
+        }
+        writer.write_all(&row)?;
+    }
+    Ok(())
+}
+
+// https://www.w3.org/TR/png/
+// the image data is stored with uncompressed deflate blocks so no compression library is needed
+pub fn write_png<W: Write> (framebuffer: &Framebuffer, writer: &mut W) -> Result<()> {
+    writer.write_all(&[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a])?;
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&framebuffer.width.to_be_bytes());
+    header.extend_from_slice(&framebuffer.height.to_be_bytes());
+    // 8 bit depth, truecolor, deflate, no filtering, no interlacing
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+    write_png_chunk(writer, b"IHDR", &header)?;
+
+    let mut raw = Vec::with_capacity((framebuffer.pitch() + 1) * framebuffer.height as usize);
+    for row in framebuffer.color.chunks_exact(framebuffer.pitch().max(1)) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+    // decoders join consecutive IDAT chunks back together, which keeps large images under the chunk size limit
+    for data in zlib_store(&raw).chunks(MAX_PNG_CHUNK) {
+        write_png_chunk(writer, b"IDAT", data)?;
+    }
+    write_png_chunk(writer, b"IEND", &[])
+}
+
+// chunk lengths are limited to 2^31 - 1 bytes
+const MAX_PNG_CHUNK: usize = 0x7fff_ffff;
+
+fn write_png_chunk<W: Write> (writer: &mut W, kind: &[u8; 4], data: &[u8]) -> Result<()> {
+    let length = u32::try_from(data.len()).ok()
+        .filter(|&length| length as usize <= MAX_PNG_CHUNK)
+        .ok_or_else(|| Error::format(format!("a {} byte PNG chunk is too large", data.len())))?;
+    writer.write_all(&length.to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    let crc = crc32(kind.iter().chain(data.iter()));
+    writer.write_all(&crc.to_be_bytes())?;
+    Ok(())
+}
+
+// https://www.rfc-editor.org/rfc/rfc1950 and https://www.rfc-editor.org/rfc/rfc1951
+fn zlib_store(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 65535;
+    let mut out = Vec::with_capacity(data.len() + data.len() / MAX_BLOCK * 5 + 11);
+    out.extend_from_slice(&[0x78, 0x01]);
+    let blocks: Vec<&[u8]> = if data.is_empty() { vec![&[]] } else { data.chunks(MAX_BLOCK).collect() };
+    for (i, block) in blocks.iter().enumerate() {
+        let last = i + 1 == blocks.len();
+        out.push(last as u8);
+        out.extend_from_slice(&(block.len() as u16).to_le_bytes());
+        out.extend_from_slice(&(!(block.len() as u16)).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+pub fn crc32<'a> (bytes: impl Iterator<Item = &'a u8>) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+pub fn adler32(bytes: &[u8]) -> u32 {
+    let mut a = 1u32;
+    let mut b = 0u32;
+    for byte in bytes {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
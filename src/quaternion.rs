@@ -0,0 +1,211 @@
+use crate::matrix::Matrix4x4;
+use crate::vector3::Vector3;
+
+use std::ops::Mul;
+
+// a rotation as a unit quaternion, a * b rotates by b first and then by a, the same as with matrices
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Quaternion {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+// which world axis euler angles rotate around first, Xyz rotates around x, then y, then z
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum EulerOrder {
+    Xyz,
+    Xzy,
+    Yxz,
+    Yzx,
+    Zxy,
+    Zyx,
+}
+
+impl EulerOrder {
+    pub const ALL: [EulerOrder; 6] = [EulerOrder::Xyz, EulerOrder::Xzy, EulerOrder::Yxz, EulerOrder::Yzx, EulerOrder::Zxy, EulerOrder::Zyx];
+
+    // axis indices in the order they are applied
+    fn axes(self) -> [usize; 3] {
+        match self {
+            EulerOrder::Xyz => [0, 1, 2],
+            EulerOrder::Xzy => [0, 2, 1],
+            EulerOrder::Yxz => [1, 0, 2],
+            EulerOrder::Yzx => [1, 2, 0],
+            EulerOrder::Zxy => [2, 0, 1],
+            EulerOrder::Zyx => [2, 1, 0],
+        }
+    }
+}
+
+impl Quaternion {
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    pub fn identity() -> Self {
+        Self::new(0.0, 0.0, 0.0, 1.0)
+    }
+
+    // rotates counterclockwise by angle radians when looking down the axis towards the origin
+    pub fn from_axis_angle(axis: &Vector3, angle: f32) -> Self {
+        let axis = axis.normalized() * (angle / 2.0).sin();
+        Self::new(axis.x, axis.y, axis.z, (angle / 2.0).cos())
+    }
+
+    // turns +z towards forward while keeping +y as close to up as it can, the basis of Camera::looking_at
+    pub fn look_rotation(forward: &Vector3, up: &Vector3) -> Self {
+        let forward = forward.normalized();
+        let mut right = up.cross(&forward);
+        // looking straight along up leaves no plane to keep it in, any sideways axis will do
+        if right.length_squared() < 0.000001 {
+            let fallback = if forward.z.abs() < 0.9 { Vector3::new(0.0, 0.0, 1.0) } else { Vector3::new(1.0, 0.0, 0.0) };
+            right = fallback.cross(&forward);
+        }
+        let right = right.normalized();
+        let up = forward.cross(&right);
+        Self::from_matrix(&Matrix4x4 { m: [
+            [right.x, up.x, forward.x, 0.0],
+            [right.y, up.y, forward.y, 0.0],
+            [right.z, up.z, forward.z, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]})
+    }
+
+    // the angle is between 0 and 2 pi, no rotation at all is reported around x
+    pub fn to_axis_angle(&self) -> (Vector3, f32) {
+        let q = self.normalized();
+        let angle = 2.0 * q.w.clamp(-1.0, 1.0).acos();
+        let sin_half = (1.0 - q.w * q.w).max(0.0).sqrt();
+        if sin_half < 0.0001 {
+            return (Vector3::new(1.0, 0.0, 0.0), 0.0);
+        }
+        (Vector3::new(q.x, q.y, q.z) / sin_half, angle)
+    }
+
+    // angles.x is always the rotation around x, the order only decides which one is applied first
+    pub fn from_euler(angles: &Vector3, order: EulerOrder) -> Self {
+        let [first, second, third] = order.axes().map(|axis| {
+            let mut unit = Vector3::new(0.0, 0.0, 0.0);
+            unit[axis] = 1.0;
+            Self::from_axis_angle(&unit, angles[axis])
+        });
+        third * second * first
+    }
+
+    // the inverse of from_euler, when the middle rotation is a quarter turn the last one is folded into the first
+    pub fn to_euler(&self, order: EulerOrder) -> Vector3 {
+        let m = self.to_matrix().m;
+        let [i, j, k] = order.axes();
+        // orders that are a rotation of xyz differ from the others by the signs of the off diagonal terms
+        let sign = if matches!(order, EulerOrder::Xyz | EulerOrder::Yzx | EulerOrder::Zxy) { 1.0 } else { -1.0 };
+
+        // atan2 stays accurate near a quarter turn where asin of the sine alone would not
+        let cos_middle = m[i][i].hypot(m[j][i]);
+        let mut angles = Vector3::new(0.0, 0.0, 0.0);
+        angles[j] = (-sign * m[k][i]).atan2(cos_middle);
+        if cos_middle > 0.001 {
+            angles[i] = (sign * m[k][j]).atan2(m[k][k]);
+            angles[k] = (sign * m[j][i]).atan2(m[i][i]);
+        } else {
+            angles[i] = (-sign * m[j][k]).atan2(m[j][j]);
+        }
+        angles
+    }
+
+    // expects a pure rotation, any scale has to be taken out first
+    pub fn from_matrix(matrix: &Matrix4x4) -> Self {
+        let m = &matrix.m;
+        let trace = m[0][0] + m[1][1] + m[2][2];
+        // builds from the largest component so the divide stays well away from zero
+        let q = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Self::new((m[2][1] - m[1][2]) / s, (m[0][2] - m[2][0]) / s, (m[1][0] - m[0][1]) / s, s / 4.0)
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = (1.0 + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.0;
+            Self::new(s / 4.0, (m[0][1] + m[1][0]) / s, (m[0][2] + m[2][0]) / s, (m[2][1] - m[1][2]) / s)
+        } else if m[1][1] > m[2][2] {
+            let s = (1.0 + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.0;
+            Self::new((m[0][1] + m[1][0]) / s, s / 4.0, (m[1][2] + m[2][1]) / s, (m[0][2] - m[2][0]) / s)
+        } else {
+            let s = (1.0 + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.0;
+            Self::new((m[0][2] + m[2][0]) / s, (m[1][2] + m[2][1]) / s, s / 4.0, (m[1][0] - m[0][1]) / s)
+        };
+        q.normalized()
+    }
+
+    pub fn to_matrix(&self) -> Matrix4x4 {
+        let Self { x, y, z, w } = *self;
+        Matrix4x4 { m: [
+            [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - z * w), 2.0 * (x * z + y * w), 0.0],
+            [2.0 * (x * y + z * w), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - x * w), 0.0],
+            [2.0 * (x * z - y * w), 2.0 * (y * z + x * w), 1.0 - 2.0 * (x * x + y * y), 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]}
+    }
+
+    pub fn dot(&self, other: &Quaternion) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    pub fn length(&self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    // repeated products drift away from unit length, this pulls them back
+    pub fn normalized(&self) -> Self {
+        let length = self.length();
+        Self::new(self.x / length, self.y / length, self.z / length, self.w / length)
+    }
+
+    // the opposite rotation, for unit quaternions this is the inverse
+    pub fn conjugate(&self) -> Self {
+        Self::new(-self.x, -self.y, -self.z, self.w)
+    }
+
+    pub fn rotate(&self, v: &Vector3) -> Vector3 {
+        // v + 2w(u x v) + 2u x (u x v) with u the vector part, cheaper than building the matrix
+        let u = Vector3::new(self.x, self.y, self.z);
+        let t = u.cross(v) * 2.0;
+        *v + t * self.w + u.cross(&t)
+    }
+
+    // constant speed interpolation along the shorter way around, t = 0 gives self and t = 1 gives other
+    pub fn slerp(&self, other: &Quaternion, t: f32) -> Self {
+        let mut other = *other;
+        let mut cos_theta = self.dot(&other);
+        // q and -q are the same rotation, flipping one takes the short way
+        if cos_theta < 0.0 {
+            other = Self::new(-other.x, -other.y, -other.z, -other.w);
+            cos_theta = -cos_theta;
+        }
+        // nearly parallel, sin(theta) would be too small to divide by
+        let (a, b) = if cos_theta > 0.9995 {
+            (1.0 - t, t)
+        } else {
+            let theta = cos_theta.acos();
+            let sin_theta = theta.sin();
+            (((1.0 - t) * theta).sin() / sin_theta, (t * theta).sin() / sin_theta)
+        };
+        Self::new(
+            self.x * a + other.x * b,
+            self.y * a + other.y * b,
+            self.z * a + other.z * b,
+            self.w * a + other.w * b,
+        ).normalized()
+    }
+}
+
+impl Mul for Quaternion {
+    type Output = Quaternion;
+
+    fn mul(self, q: Quaternion) -> Quaternion {
+        Quaternion::new(
+            self.w * q.x + self.x * q.w + self.y * q.z - self.z * q.y,
+            self.w * q.y - self.x * q.z + self.y * q.w + self.z * q.x,
+            self.w * q.z + self.x * q.y - self.y * q.x + self.z * q.w,
+            self.w * q.w - self.x * q.x - self.y * q.y - self.z * q.z,
+        )
+    }
+}
@@ -0,0 +1,129 @@
+use crate::camera::Camera;
+use crate::error::{Error, Result};
+use crate::framebuffer::Framebuffer;
+use crate::graphics::project_geometry;
+use crate::light::Lighting;
+use crate::material::Material;
+use crate::matrix::Matrix4x4;
+use crate::mesh::{Mesh, normal_matrix, transform_point};
+use crate::triangle::intersect_ray;
+use crate::vector3::Vector3;
+
+// a node of the scene graph, the mesh's transform is relative to the parent node if it has one,
+// a mesh without geometry works as an invisible pivot for its children
+pub struct SceneObject {
+    pub mesh: Mesh,
+    pub material: Material,
+    pub parent: Option<usize>,
+}
+
+// object to world transform of a node with everything above it applied
+#[derive(Copy, Clone, Debug)]
+pub struct WorldTransform {
+    pub model_matrix: Matrix4x4,
+    pub normal_matrix: Matrix4x4,
+}
+
+pub struct Scene {
+    // parents always come before their children, so the tree can be walked in order
+    pub objects: Vec<SceneObject>,
+    pub lighting: Lighting,
+    pub camera: Camera,
+    pub background: (u8, u8, u8),
+}
+
+impl Scene {
+    pub fn new (camera: Camera, lighting: Lighting, background: (u8, u8, u8)) -> Self {
+        Self { objects: Vec::new(), lighting, camera, background }
+    }
+
+    // returns the index of the new object so callers can find it again later
+    pub fn add (&mut self, mesh: Mesh, material: Material) -> usize {
+        self.objects.push(SceneObject { mesh, material, parent: None });
+        self.objects.len() - 1
+    }
+
+    // the child follows every transform of its parent, e.g. a lid sitting on a teapot
+    pub fn add_child (&mut self, parent: usize, mesh: Mesh, material: Material) -> Result<usize> {
+        if parent >= self.objects.len() {
+            return Err(Error::Render(format!("parent {} is not in the scene", parent)));
+        }
+        self.objects.push(SceneObject { mesh, material, parent: Some(parent) });
+        Ok(self.objects.len() - 1)
+    }
+
+    pub fn children (&self, parent: usize) -> impl Iterator<Item = usize> + '_ {
+        self.objects.iter()
+            .enumerate()
+            .filter(move |(_, object)| object.parent == Some(parent))
+            .map(|(index, _)| index)
+    }
+
+    pub fn materials_mut (&mut self) -> impl Iterator<Item = &mut Material> {
+        self.objects.iter_mut().map(|object| &mut object.material)
+    }
+
+    // world transforms of every object, in the same order as objects
+    pub fn world_transforms (&self) -> Vec<WorldTransform> {
+        let mut transforms: Vec<WorldTransform> = Vec::with_capacity(self.objects.len());
+        for object in &self.objects {
+            let model_matrix = match object.parent {
+                Some(parent) => transforms[parent].model_matrix * object.mesh.model_matrix(),
+                None => object.mesh.model_matrix(),
+            };
+            transforms.push(WorldTransform { model_matrix, normal_matrix: normal_matrix(&model_matrix) });
+        }
+        transforms
+    }
+
+    pub fn world_transform (&self, index: usize) -> WorldTransform {
+        self.world_transforms()[index]
+    }
+
+    // average of every object's world space origin, what an orbiting camera circles by default
+    pub fn center (&self) -> Option<Vector3> {
+        if self.objects.is_empty() {
+            return None;
+        }
+        let origin = Vector3::new(0.0, 0.0, 0.0);
+        let sum = self.world_transforms().iter()
+            .fold(origin, |sum, transform| sum + transform.model_matrix.transform_point(&origin));
+        Some(sum / self.objects.len() as f32)
+    }
+
+    // the object under a pixel of a width by height image, the nearest one when several overlap
+    pub fn pick (&self, x: f32, y: f32, width: u32, height: u32) -> Option<usize> {
+        let (origin, direction) = self.camera.screen_ray(x, y, width, height)?;
+        let mut nearest: Option<(usize, f32)> = None;
+        for (index, (object, transform)) in self.objects.iter().zip(self.world_transforms()).enumerate() {
+            for mut triangle in object.mesh.triangles() {
+                for vertex in &mut triangle.vertices {
+                    *vertex = transform_point(&transform.model_matrix, vertex);
+                }
+                if let Some(distance) = intersect_ray(&triangle, &origin, &direction) {
+                    if nearest.is_none_or(|(_, nearest_distance)| distance < nearest_distance) {
+                        nearest = Some((index, distance));
+                    }
+                }
+            }
+        }
+        nearest.map(|(index, _)| index)
+    }
+}
+
+// clears the target to the scene background and draws every object through the active camera
+pub fn render (scene: &Scene, framebuffer: &mut Framebuffer) {
+    framebuffer.depth_compare = scene.camera.depth_compare();
+    framebuffer.clear(scene.background);
+    for (object, transform) in scene.objects.iter().zip(scene.world_transforms()) {
+        project_geometry(
+            framebuffer,
+            &scene.camera,
+            &object.mesh.geometry,
+            &transform.model_matrix,
+            &transform.normal_matrix,
+            &object.material,
+            &scene.lighting,
+        );
+    }
+}
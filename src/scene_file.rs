@@ -0,0 +1,444 @@
+// a small subset of TOML describing a whole scene, every accepted field is shown below and only the camera's
+// h_fov, v_fov, z_near and z_far, a light's direction and a mesh's file or builtin are required
+//
+//   background = [255, 255, 255]
+//
+//   [camera]
+//   position = [0, 0, -5]
+//   rotation = [0, 0, 0]  # or target = [0, 0, 10] to look at a point
+//   rotation_order = "xyz"
+//   h_fov = 100  # both fovs in degrees between 0 and 180
+//   v_fov = 77.7
+//   z_near = 1  # greater than zero and closer than z_far
+//   z_far = 200
+//   projection = "perspective"  # or "orthographic" with a height in world units
+//   height = 10  # only for orthographic cameras
+//   reversed_z = false
+//
+//   [ambient]
+//   color = [255, 255, 255]
+//   intensity = 0.25
+//
+//   [[light]]
+//   direction = [1, -1, 1]
+//   color = [255, 255, 255]
+//   intensity = 0.85
+//
+//   [[mesh]]
+//   file = "models/teapot.obj"  # .obj or .tris, or builtin = "teapot" or "cube"
+//   crease_angle = 60  # smooths normals up to this angle in degrees, models without normals are faceted otherwise
+//   position = [0, 0, 10]
+//   rotation = [0, 0, 0]
+//   rotation_order = "xyz"
+//   scale = [1, 1, 1]
+//   color = [255, 127, 127]
+//   shading = "phong"  # or "flat" or "gouraud"
+//   specular = 0.5  # no highlight when 0
+//   shininess = 32
+//   outline = false
+//   texture = "textures/checker.ppm"  # .ppm or .bmp, needs texture coordinates in the model
+//   filter = "bilinear"  # or "nearest"
+//   name = "teapot"
+//
+//   [[mesh]]
+//   builtin = "cube"
+//   parent = "teapot"
+//
+// rotations are given in degrees and applied around x, then y, then z unless a rotation_order such as "zyx" says otherwise,
+// relative paths are resolved against the scene file's directory and a mesh with a parent is placed relative to it,
+// the parent has to be defined further up the file
+use crate::camera::{Camera, Projection};
+use crate::quaternion::{EulerOrder, Quaternion};
+use crate::error::{Error, Result};
+use crate::light::{AmbientLight, DirectionalLight, Lighting};
+use crate::material::{Material, Shading};
+use crate::mesh::{Mesh, cube, teapot};
+use crate::assets::load_model;
+use crate::scene::Scene;
+use crate::texture::{Filter, load_texture};
+use crate::vector3::Vector3;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+#[derive(Clone, Debug)]
+enum Value {
+    Number(f32),
+    Text(String),
+    Boolean(bool),
+    List(Vec<f32>),
+}
+
+impl Value {
+    fn kind(&self) -> &'static str {
+        match self {
+            Value::Number(_) => "a number",
+            Value::Text(_) => "a string",
+            Value::Boolean(_) => "a boolean",
+            Value::List(_) => "a list",
+        }
+    }
+}
+
+struct Field {
+    key: String,
+    value: Value,
+    line: usize,
+}
+
+// one [table] or [[table]] entry, fields are removed as they are read so leftovers can be reported
+struct Table {
+    name: String,
+    line: usize,
+    fields: Vec<Field>,
+}
+
+impl Table {
+    fn take(&mut self, key: &str) -> Option<Field> {
+        let index = self.fields.iter().position(|field| field.key == key)?;
+        Some(self.fields.remove(index))
+    }
+
+    fn number(&mut self, key: &str) -> Result<Option<f32>> {
+        Ok(self.located_number(key)?.map(|(number, _)| number))
+    }
+
+    // the line comes along for values that still have to be range checked
+    fn located_number(&mut self, key: &str) -> Result<Option<(f32, usize)>> {
+        match self.take(key) {
+            Some(Field { value: Value::Number(number), line, .. }) => Ok(Some((number, line))),
+            Some(field) => Err(expected(&field, "a number")),
+            None => Ok(None),
+        }
+    }
+
+    fn text(&mut self, key: &str) -> Result<Option<(String, usize)>> {
+        match self.take(key) {
+            Some(Field { value: Value::Text(text), line, .. }) => Ok(Some((text, line))),
+            Some(field) => Err(expected(&field, "a string")),
+            None => Ok(None),
+        }
+    }
+
+    fn boolean(&mut self, key: &str) -> Result<Option<bool>> {
+        match self.take(key) {
+            Some(Field { value: Value::Boolean(boolean), .. }) => Ok(Some(boolean)),
+            Some(field) => Err(expected(&field, "true or false")),
+            None => Ok(None),
+        }
+    }
+
+    fn vector(&mut self, key: &str) -> Result<Option<Vector3>> {
+        match self.take(key) {
+            Some(Field { value: Value::List(list), .. }) if list.len() == 3 => Ok(Some(Vector3::new(list[0], list[1], list[2]))),
+            Some(field) => Err(expected(&field, "a list of three numbers")),
+            None => Ok(None),
+        }
+    }
+
+    fn color(&mut self, key: &str) -> Result<Option<(u8, u8, u8)>> {
+        match self.take(key) {
+            Some(Field { value: Value::List(list), .. })
+                if list.len() == 3 && list.iter().all(|channel| (0.0..=255.0).contains(channel) && channel.fract() == 0.0) =>
+            {
+                Ok(Some((list[0] as u8, list[1] as u8, list[2] as u8)))
+            },
+            Some(field) => Err(expected(&field, "a list of three whole numbers from 0 to 255")),
+            None => Ok(None),
+        }
+    }
+
+    fn required<T> (&self, value: Option<T>, key: &str) -> Result<T> {
+        value.ok_or_else(|| parse_error(self.line, &format!("[{}] is missing field \"{}\"", self.name, key)))
+    }
+
+    // every field should have been read by now, anything left over is a typo
+    fn finish(self) -> Result<()> {
+        match self.fields.first() {
+            Some(field) => Err(field_error(field.line, &field.key, &format!("unknown field in [{}]", self.name))),
+            None => Ok(()),
+        }
+    }
+}
+
+// reads a scene file, mesh and texture paths inside it are relative to the file
+pub fn load_scene(path: &Path) -> Result<Scene> {
+    let text = fs::read_to_string(path)?;
+    let directory = path.parent().unwrap_or(Path::new(""));
+    parse_scene(&text, directory)
+}
+
+pub fn parse_scene(text: &str, directory: &Path) -> Result<Scene> {
+    let tables = parse_tables(text)?;
+
+    let mut camera = None;
+    let mut background = (255, 255, 255);
+    let mut ambient = AmbientLight { color: (255, 255, 255), intensity: 0.25 };
+    let mut lights = Vec::new();
+    let mut objects = Vec::new();
+    let mut names: HashMap<String, usize> = HashMap::new();
+
+    for mut table in tables {
+        match table.name.as_str() {
+            "" => {
+                background = table.color("background")?.unwrap_or(background);
+            },
+            "camera" => {
+                camera = Some(parse_camera(&mut table)?);
+            },
+            "ambient" => {
+                ambient.color = table.color("color")?.unwrap_or(ambient.color);
+                ambient.intensity = table.number("intensity")?.unwrap_or(ambient.intensity);
+            },
+            "light" => {
+                let direction = table.vector("direction")?;
+                let direction = table.required(direction, "direction")?;
+                let color = table.color("color")?.unwrap_or((255, 255, 255));
+                let intensity = table.number("intensity")?.unwrap_or(1.0);
+                lights.push(DirectionalLight::new(direction, color, intensity));
+            },
+            "mesh" => {
+                let parent = match table.text("parent")? {
+                    Some((parent, line)) => match names.get(&parent) {
+                        Some(&index) => Some(index),
+                        None => return Err(field_error(line, "parent", &format!("no mesh named \"{}\" above this one", parent))),
+                    },
+                    None => None,
+                };
+                if let Some((name, line)) = table.text("name")? {
+                    if names.insert(name.clone(), objects.len()).is_some() {
+                        return Err(field_error(line, "name", &format!("\"{}\" is used by another mesh", name)));
+                    }
+                }
+                let (mesh, material) = parse_mesh(&mut table, directory)?;
+                objects.push((mesh, material, parent));
+            },
+            _ => unreachable!("parse_tables only accepts known tables"),
+        }
+        table.finish()?;
+    }
+
+    let camera = camera.ok_or_else(|| parse_error(text.lines().count(), "the scene has no [camera] table"))?;
+    let mut scene = Scene::new(camera, Lighting::new(ambient, lights), background);
+    for (mesh, material, parent) in objects {
+        match parent {
+            Some(parent) => scene.add_child(parent, mesh, material)?,
+            None => scene.add(mesh, material),
+        };
+    }
+    Ok(scene)
+}
+
+fn parse_camera(table: &mut Table) -> Result<Camera> {
+    let position = table.vector("position")?.unwrap_or(Vector3::new(0.0, 0.0, 0.0));
+    let rotation = parse_rotation(table)?;
+    let target = table.vector("target")?;
+    let h_fov = table.located_number("h_fov")?;
+    let v_fov = table.located_number("v_fov")?;
+    let z_near = table.located_number("z_near")?;
+    let z_far = table.number("z_far")?;
+    let ((h_fov, h_fov_line), (v_fov, v_fov_line)) = (table.required(h_fov, "h_fov")?, table.required(v_fov, "v_fov")?);
+    let ((z_near, near_line), z_far) = (table.required(z_near, "z_near")?, table.required(z_far, "z_far")?);
+    // the same limits the command line puts on --fov, --near and --far
+    for (key, fov, line) in [("h_fov", h_fov, h_fov_line), ("v_fov", v_fov, v_fov_line)] {
+        if !(fov > 0.0 && fov < 180.0) {
+            return Err(field_error(line, key, &format!("must be between 0 and 180 degrees but got {}", fov)));
+        }
+    }
+    if !(z_near > 0.0 && z_near.is_finite()) {
+        return Err(field_error(near_line, "z_near", &format!("must be greater than zero but got {}", z_near)));
+    }
+    if z_near >= z_far || z_far.is_nan() {
+        return Err(field_error(near_line, "z_near", &format!("({}) must be closer than z_far ({})", z_near, z_far)));
+    }
+    let mut camera = match (target, rotation) {
+        (Some(_), Some(_)) => return Err(parse_error(table.line, "[camera] can't have both \"rotation\" and \"target\"")),
+        (Some(target), None) => Camera::looking_at(position, &target, &Vector3::new(0.0, 1.0, 0.0), h_fov, v_fov, z_near, z_far),
+        (None, rotation) => Camera::new(position, rotation.unwrap_or(Quaternion::identity()), h_fov, v_fov, z_near, z_far),
+    };
+    camera.reversed_z = table.boolean("reversed_z")?.unwrap_or(false);
+    let height = table.located_number("height")?;
+    if let Some((height, line)) = height {
+        if !(height > 0.0 && height.is_finite()) {
+            return Err(field_error(line, "height", &format!("must be greater than zero but got {}", height)));
+        }
+    }
+    let height = height.map(|(height, _)| height);
+    if let Some((projection, line)) = table.text("projection")? {
+        camera.projection = match projection.as_str() {
+            "perspective" => Projection::Perspective,
+            "orthographic" => Projection::Orthographic {
+                height: height.ok_or_else(|| field_error(line, "projection", "an orthographic camera needs a \"height\" field"))?,
+            },
+            _ => return Err(field_error(line, "projection", "expected \"perspective\" or \"orthographic\"")),
+        };
+    }
+    if height.is_some() && camera.projection == Projection::Perspective {
+        return Err(parse_error(table.line, "\"height\" only applies to a camera with projection = \"orthographic\""));
+    }
+    Ok(camera)
+}
+
+fn parse_mesh(table: &mut Table, directory: &Path) -> Result<(Mesh, Material)> {
+    let mut mesh = match (table.text("file")?, table.text("builtin")?) {
+        (Some((file, line)), None) => load_model(&directory.join(&file))
+            .map_err(|error| field_error(line, "file", &format!("could not load \"{}\": {}", file, error)))?,
+        (None, Some((builtin, line))) => match builtin.as_str() {
+            "teapot" => teapot()?,
+            "cube" => cube(1.0),
+            _ => return Err(field_error(line, "builtin", &format!("unknown mesh \"{}\", expected \"teapot\" or \"cube\"", builtin))),
+        },
+        (Some(_), Some(_)) => return Err(parse_error(table.line, "[[mesh]] can't have both \"file\" and \"builtin\"")),
+        (None, None) => return Err(parse_error(table.line, "[[mesh]] needs a \"file\" or \"builtin\" field")),
+    };
+
+    if let Some(crease_angle) = table.number("crease_angle")? {
+        mesh.generate_normals(crease_angle.to_radians());
+    }
+    if let Some(position) = table.vector("position")? {
+        mesh.position = position;
+    }
+    if let Some(rotation) = parse_rotation(table)? {
+        mesh.rotation = rotation;
+    }
+    if let Some(scale) = table.vector("scale")? {
+        mesh.scale = scale;
+    }
+
+    let mut material = Material::new(table.color("color")?.unwrap_or((255, 255, 255)));
+    material.outline = table.boolean("outline")?.unwrap_or(material.outline);
+    material.specular = table.number("specular")?.unwrap_or(material.specular);
+    material.shininess = table.number("shininess")?.unwrap_or(material.shininess);
+    if let Some((shading, line)) = table.text("shading")? {
+        material.shading = match shading.as_str() {
+            "flat" => Shading::Flat,
+            "gouraud" => Shading::Gouraud,
+            "phong" => Shading::Phong,
+            _ => return Err(field_error(line, "shading", "expected \"flat\", \"gouraud\" or \"phong\"")),
+        };
+    }
+    if let Some((texture, line)) = table.text("texture")? {
+        let texture = load_texture(&directory.join(&texture))
+            .map_err(|error| field_error(line, "texture", &format!("could not load \"{}\": {}", texture, error)))?;
+        material.texture = Some(Arc::new(texture));
+    }
+    if let Some((filter, line)) = table.text("filter")? {
+        material.filter = match filter.as_str() {
+            "nearest" => Filter::Nearest,
+            "bilinear" => Filter::Bilinear,
+            _ => return Err(field_error(line, "filter", "expected \"nearest\" or \"bilinear\"")),
+        };
+    }
+    Ok((mesh, material))
+}
+
+fn parse_tables(text: &str) -> Result<Vec<Table>> {
+    let mut tables = vec![Table { name: String::new(), line: 1, fields: Vec::new() }];
+
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix("[[").and_then(|line| line.strip_suffix("]]")) {
+            let name = name.trim();
+            if !matches!(name, "light" | "mesh") {
+                return Err(parse_error(line_number, &format!("unknown table array [[{}]], expected [[light]] or [[mesh]]", name)));
+            }
+            tables.push(Table { name: name.to_string(), line: line_number, fields: Vec::new() });
+        } else if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            let name = name.trim();
+            if !matches!(name, "camera" | "ambient") {
+                return Err(parse_error(line_number, &format!("unknown table [{}], expected [camera] or [ambient]", name)));
+            }
+            if tables.iter().any(|table| table.name == name) {
+                return Err(parse_error(line_number, &format!("[{}] is defined twice", name)));
+            }
+            tables.push(Table { name: name.to_string(), line: line_number, fields: Vec::new() });
+        } else {
+            let (key, value) = line.split_once('=')
+                .ok_or_else(|| parse_error(line_number, &format!("expected \"key = value\" but found \"{}\"", line)))?;
+            let key = key.trim();
+            let value = parse_value(value.trim())
+                .map_err(|message| field_error(line_number, key, &message))?;
+            let table = tables.last_mut().unwrap();
+            if table.fields.iter().any(|field| field.key == key) {
+                return Err(field_error(line_number, key, "field is set twice"));
+            }
+            table.fields.push(Field { key: key.to_string(), value, line: line_number });
+        }
+    }
+    Ok(tables)
+}
+
+fn parse_value(text: &str) -> std::result::Result<Value, String> {
+    if let Some(text) = text.strip_prefix('"') {
+        return match text.strip_suffix('"') {
+            Some(text) if !text.contains('"') => Ok(Value::Text(text.to_string())),
+            _ => Err(String::from("unterminated string")),
+        };
+    }
+    if let Some(list) = text.strip_prefix('[') {
+        let list = list.strip_suffix(']').ok_or_else(|| String::from("unterminated list"))?;
+        return list.split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| item.parse::<f32>().map_err(|_| format!("expected a number in the list but found \"{}\"", item)))
+            .collect::<std::result::Result<Vec<f32>, String>>()
+            .map(Value::List);
+    }
+    match text {
+        "true" => Ok(Value::Boolean(true)),
+        "false" => Ok(Value::Boolean(false)),
+        _ => text.parse::<f32>()
+            .map(Value::Number)
+            .map_err(|_| format!("can't read value \"{}\"", text)),
+    }
+}
+
+// a # starts a comment unless it is inside a string
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (index, character) in line.char_indices() {
+        match character {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..index],
+            _ => {},
+        }
+    }
+    line
+}
+
+// euler angles in degrees, rotation_order picks which axis is turned around first
+fn parse_rotation(table: &mut Table) -> Result<Option<Quaternion>> {
+    let rotation = table.vector("rotation")?;
+    let order = match table.text("rotation_order")? {
+        Some((order, line)) => match order.as_str() {
+            "xyz" => EulerOrder::Xyz,
+            "xzy" => EulerOrder::Xzy,
+            "yxz" => EulerOrder::Yxz,
+            "yzx" => EulerOrder::Yzx,
+            "zxy" => EulerOrder::Zxy,
+            "zyx" => EulerOrder::Zyx,
+            _ => return Err(field_error(line, "rotation_order", "expected one of \"xyz\", \"xzy\", \"yxz\", \"yzx\", \"zxy\" or \"zyx\"")),
+        },
+        None => EulerOrder::Xyz,
+    };
+    Ok(rotation.map(|degrees| Quaternion::from_euler(&Vector3::new(degrees.x.to_radians(), degrees.y.to_radians(), degrees.z.to_radians()), order)))
+}
+
+fn parse_error(line_number: usize, message: &str) -> Error {
+    Error::parse(line_number, message)
+}
+
+fn field_error(line_number: usize, key: &str, message: &str) -> Error {
+    parse_error(line_number, &format!("field \"{}\": {}", key, message))
+}
+
+fn expected(field: &Field, description: &str) -> Error {
+    field_error(field.line, &field.key, &format!("expected {} but found {}", description, field.value.kind()))
+}
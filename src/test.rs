@@ -1,6 +1,34 @@
 use crate::matrix::*;
-use crate::vector3::Vector3;
-use crate::mesh::cube;
+use crate::quaternion::{EulerOrder, Quaternion};
+use crate::vector2::Vector2;
+use crate::vector3::{Vector3, subtract, calculate_magnitude, cross_product, dot_product};
+use crate::vector4::Vector4;
+use crate::triangle::calculate_normal;
+use crate::material::{Material, Shading};
+use crate::texture::{Texture, Filter, load_texture, parse_bmp, parse_ppm};
+use crate::mesh::{cube, teapot, parse_tris, transform_normal, transform_point};
+use crate::assets::{AssetPaths, load_model};
+use crate::error::{Error, Result};
+use crate::indexed_mesh::{IndexedMesh, Vertex};
+use crate::camera::{Camera, Projection, vertical_fov};
+use crate::controller::{FlyController, OrbitController};
+use crate::light::{Lighting, AmbientLight, DirectionalLight};
+use crate::framebuffer::{DepthCompare, Framebuffer};
+use crate::graphics::{ScreenPoint, fill_triangle, rasterize_triangle};
+use crate::output::{render_to_file, save_image, write_ppm, write_bmp, write_png, crc32, adler32};
+use crate::obj::parse_obj;
+use crate::clipping::{ClipVertex, CLIP_PLANES, clip_triangle};
+use crate::scene::{Scene, render};
+use crate::scene_file::parse_scene;
+use std::path::{Path, PathBuf};
+
+// at the origin looking down +z with a square 90 degree view
+fn origin_camera () -> Camera {
+    Camera::new(Vector3::new(0.0, 0.0, 0.0), Quaternion::identity(), 90.0, 90.0, 0.1, 100.0)
+}
+
+// the smallest [camera] table a scene file accepts, five lines long
+const CAMERA_TABLE: &str = "[camera]\nh_fov = 90\nv_fov = 90\nz_near = 1\nz_far = 9\n";
 
 #[test]
 fn matrix_scale_test () {
@@ -135,12 +163,13 @@ fn matrix_vector_multiply_test () {
 fn mesh_translate_test () {
     let mut cube1 = cube(1.0);
     let cube2 = cube(1.0);
+    let original = cube2.triangles();
     cube1.translate(10.0, -10.0, 30.3);
-    for (i, triangle) in cube1.triangles.iter().enumerate() {
+    for (i, triangle) in cube1.world_triangles().iter().enumerate() {
         for (j, vertex) in triangle.vertices.iter().enumerate() {
-            let x = cube2.triangles[i].vertices[j].x;
-            let y = cube2.triangles[i].vertices[j].y;
-            let z = cube2.triangles[i].vertices[j].z;
+            let x = original[i].vertices[j].x;
+            let y = original[i].vertices[j].y;
+            let z = original[i].vertices[j].z;
             assert_eq!(vertex.x, x + 10.0);
             assert_eq!(vertex.y, y - 10.0);
             assert_eq!(vertex.z, z + 30.3);
@@ -150,3 +179,979 @@ fn mesh_translate_test () {
     assert_eq!(cube1.position.y, cube2.position.y - 10.0);
     assert_eq!(cube1.position.z, cube2.position.z + 30.3);
 }
+
+#[test]
+fn mesh_rotate_test () {
+    let mut cube1 = cube(1.0);
+    let cube2 = cube(1.0);
+    let original = cube2.triangles();
+    cube1.translate(5.0, 0.0, 0.0);
+    for _ in 0..1000 {
+        cube1.rotate(0.0123, 0.0456, 0.0789);
+    }
+    // the object space vertices are never touched
+    for (i, triangle) in cube1.triangles().iter().enumerate() {
+        for (j, vertex) in triangle.vertices.iter().enumerate() {
+            assert_eq!(vertex.x, original[i].vertices[j].x);
+            assert_eq!(vertex.y, original[i].vertices[j].y);
+            assert_eq!(vertex.z, original[i].vertices[j].z);
+        }
+    }
+    // so the shape stays exact, every edge of the cube is still one unit long
+    for triangle in cube1.world_triangles() {
+        let edge = subtract(&triangle.vertices[1], &triangle.vertices[0]);
+        let length = calculate_magnitude(&edge);
+        assert!((length - 1.0).abs() < 0.0001 || (length - 2.0f32.sqrt()).abs() < 0.0001);
+    }
+    assert_eq!(cube1.position.x, 5.0);
+}
+
+#[test]
+fn mesh_global_rotate_test () {
+    let mut cube1 = cube(1.0);
+    cube1.translate(5.0, 0.0, 0.0);
+    cube1.global_rotate(0.0, 0.0, std::f32::consts::FRAC_PI_2);
+    assert!(cube1.position.x.abs() < 0.0001);
+    assert!((cube1.position.y - 5.0).abs() < 0.0001);
+    assert!((cube1.rotation.to_euler(EulerOrder::Xyz).z - std::f32::consts::FRAC_PI_2).abs() < 0.0001);
+}
+
+#[test]
+fn mesh_rotation_drift_test () {
+    // composing many small rotations keeps a unit quaternion
+    let mut mesh = cube(1.0);
+    for _ in 0..1000 {
+        mesh.rotate(0.0123, 0.0456, 0.0789);
+    }
+    assert!((mesh.rotation.length() - 1.0).abs() < 0.0001);
+}
+
+#[test]
+fn framebuffer_depth_test () {
+    let mut framebuffer = Framebuffer::new(4, 4).unwrap();
+    assert!(framebuffer.test_and_set_depth(1, 2, 0.5));
+    assert!(!framebuffer.test_and_set_depth(1, 2, 0.75));
+    assert!(framebuffer.test_and_set_depth(1, 2, 0.25));
+    assert_eq!(framebuffer.depth(1, 2), Some(0.25));
+    assert!(!framebuffer.test_and_set_depth(-1, 0, 0.0));
+    assert!(!framebuffer.test_and_set_depth(4, 0, 0.0));
+    framebuffer.clear((1, 2, 3));
+    assert_eq!(framebuffer.depth(1, 2), Some(f32::INFINITY));
+    assert_eq!(framebuffer.pixel(3, 3), Some((1, 2, 3)));
+}
+
+#[test]
+fn fill_triangle_depth_test () {
+    let mut framebuffer = Framebuffer::new(8, 8).unwrap();
+    framebuffer.clear((255, 255, 255));
+    let near = [ScreenPoint::new(0.0, 0.0, 0.25), ScreenPoint::new(7.0, 0.0, 0.25), ScreenPoint::new(0.0, 7.0, 0.25)];
+    let far = [ScreenPoint::new(0.0, 0.0, 0.75), ScreenPoint::new(7.0, 0.0, 0.75), ScreenPoint::new(0.0, 7.0, 0.75)];
+    fill_triangle(&mut framebuffer, near[0], near[1], near[2], (255, 0, 0));
+    fill_triangle(&mut framebuffer, far[0], far[1], far[2], (0, 0, 255));
+    assert_eq!(framebuffer.pixel(1, 1), Some((255, 0, 0)));
+    assert_eq!(framebuffer.pixel(7, 7), Some((255, 255, 255)));
+}
+
+#[test]
+fn clip_inside_triangle_test () {
+    let polygon = clip_triangle([
+        ClipVertex::new(Vector4::new(0.0, 0.0, 0.5, 1.0)),
+        ClipVertex::new(Vector4::new(0.5, 0.0, 0.5, 1.0)),
+        ClipVertex::new(Vector4::new(0.0, 0.5, 0.5, 1.0)),
+    ]);
+    assert_eq!(polygon.len(), 3);
+    assert!(polygon.iter().all(|vertex| vertex.edge));
+}
+
+#[test]
+fn clip_outside_triangle_test () {
+    let polygon = clip_triangle([
+        ClipVertex::new(Vector4::new(2.0, 0.0, 0.5, 1.0)),
+        ClipVertex::new(Vector4::new(3.0, 0.0, 0.5, 1.0)),
+        ClipVertex::new(Vector4::new(2.0, 0.5, 0.5, 1.0)),
+    ]);
+    assert!(polygon.is_empty());
+}
+
+#[test]
+fn clip_near_plane_test () {
+    // one vertex behind the camera, the other two in front
+    let polygon = clip_triangle([
+        ClipVertex::new(Vector4::new(0.0, 0.0, -1.0, 0.5)),
+        ClipVertex::new(Vector4::new(0.5, 0.0, 1.0, 2.0)),
+        ClipVertex::new(Vector4::new(0.0, 0.5, 1.0, 2.0)),
+    ]);
+    assert_eq!(polygon.len(), 4);
+    for vertex in &polygon {
+        for plane in CLIP_PLANES {
+            assert!(plane.distance(&vertex.position) >= -0.0001);
+        }
+    }
+    // the new edge along the near plane is not part of the original triangle
+    assert_eq!(polygon.iter().filter(|vertex| !vertex.edge).count(), 1);
+}
+
+#[test]
+fn write_ppm_test () {
+    let mut framebuffer = Framebuffer::new(2, 1).unwrap();
+    framebuffer.set_pixel(1, 0, (10, 20, 30));
+    let mut bytes = Vec::new();
+    write_ppm(&framebuffer, &mut bytes).unwrap();
+    assert_eq!(&bytes[..], b"P6\n2 1\n255\n\0\0\0\x0a\x14\x1e");
+}
+
+#[test]
+fn write_bmp_test () {
+    let mut framebuffer = Framebuffer::new(3, 2).unwrap();
+    framebuffer.set_pixel(0, 1, (10, 20, 30));
+    let mut bytes = Vec::new();
+    write_bmp(&framebuffer, &mut bytes).unwrap();
+    // rows are padded to 12 bytes and the bottom row comes first
+    assert_eq!(bytes.len(), 54 + 12 * 2);
+    assert_eq!(&bytes[0..2], b"BM");
+    assert_eq!(&bytes[54..57], &[30, 20, 10]);
+
+    // sizes that don't fit the 32 bit header fields are refused before anything is written
+    let huge = Framebuffer { width: 40000, height: 40000, color: Vec::new(), depth: Vec::new(), depth_compare: DepthCompare::Less };
+    let mut bytes = Vec::new();
+    assert!(matches!(write_bmp(&huge, &mut bytes), Err(Error::Format(_))));
+    assert!(bytes.is_empty());
+}
+
+#[test]
+fn write_png_test () {
+    let framebuffer = Framebuffer::new(4, 4).unwrap();
+    let mut bytes = Vec::new();
+    write_png(&framebuffer, &mut bytes).unwrap();
+    assert_eq!(&bytes[0..8], &[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a]);
+    assert_eq!(&bytes[bytes.len() - 12..], &[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]);
+    assert_eq!(crc32(b"123456789".iter()), 0xcbf4_3926);
+    assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+}
+
+
+#[test]
+fn parse_obj_test () {
+    let source = "\
+# a quad and a triangle in two groups
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vn 0 0 -1
+o quad
+f 1/1/1 2/1/1 3/1/1 4/1/1
+g triangle
+f -4//-1 -3//-1 -2//-1
+";
+    let groups = parse_obj(source.as_bytes()).unwrap();
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].name, "quad");
+    assert_eq!(groups[0].triangles.len(), 2);
+    assert_eq!(groups[0].triangles[1].vertices[2].y, 1.0);
+    assert_eq!(groups[1].name, "triangle");
+    assert_eq!(groups[1].triangles.len(), 1);
+    assert_eq!(groups[1].triangles[0].vertices[1].x, 1.0);
+}
+
+#[test]
+fn parse_obj_error_test () {
+    let error = parse_obj("v 0 0 0\nv 1 0 0\nf 1 2 3\n".as_bytes()).err().unwrap();
+    assert!(error.to_string().contains("line 3"));
+    let error = parse_obj("v 0 zero 0\n".as_bytes()).err().unwrap();
+    assert!(error.to_string().contains("line 1"));
+}
+
+#[test]
+fn camera_view_matrix_test () {
+    let mut camera = Camera::new(Vector3::new(0.0, 0.0, -5.0), Quaternion::identity(), 90.0, 90.0, 1.0, 100.0);
+    let point = matrix_vector_multiply(&camera.view_matrix(), &Vector3::new(0.0, 0.0, 0.0));
+    assert_eq!([point[0][0], point[1][0], point[2][0], point[3][0]], [0.0, 0.0, 5.0, 1.0]);
+
+    // turning a quarter turn to the right puts +x straight ahead
+    camera.position = Vector3::new(0.0, 0.0, 0.0);
+    camera.rotate(0.0, std::f32::consts::FRAC_PI_2, 0.0);
+    let point = matrix_vector_multiply(&camera.view_matrix(), &Vector3::new(1.0, 0.0, 0.0));
+    assert!(point[0][0].abs() < 0.0001);
+    assert!(point[1][0].abs() < 0.0001);
+    assert!((point[2][0] - 1.0).abs() < 0.0001);
+    assert!((camera.forward().x - 1.0).abs() < 0.0001);
+}
+
+#[test]
+fn lambert_shading_test () {
+    let lighting = Lighting::new(
+        AmbientLight { color: (255, 255, 255), intensity: 0.2 },
+        vec![DirectionalLight::new(Vector3::new(0.0, 0.0, 2.0), (255, 255, 255), 0.8)],
+    );
+    let material = Material::new((200, 100, 0));
+    let to_eye = Vector3::new(0.0, 0.0, -1.0);
+    // facing the light, facing away from it and side on
+    assert_eq!(lighting.shade(&Vector3::new(0.0, 0.0, -1.0), &to_eye, &material), (200, 100, 0));
+    assert_eq!(lighting.shade(&Vector3::new(0.0, 0.0, 1.0), &to_eye, &material), (40, 20, 0));
+    assert_eq!(lighting.shade(&Vector3::new(1.0, 0.0, 0.0), &to_eye, &material), (40, 20, 0));
+    assert_eq!(Lighting::unlit().shade(&Vector3::new(0.0, 0.0, 1.0), &to_eye, &material), (200, 100, 0));
+}
+
+#[test]
+fn blinn_phong_highlight_test () {
+    let lighting = Lighting::new(
+        AmbientLight { color: (255, 255, 255), intensity: 0.0 },
+        vec![DirectionalLight::new(Vector3::new(0.0, 0.0, 1.0), (255, 255, 255), 0.5)],
+    );
+    let mut material = Material::new((100, 100, 100));
+    material.specular = 1.0;
+    let normal = Vector3::new(0.0, 0.0, -1.0);
+    // looking straight down the reflection gets the full highlight, from the side it falls off
+    let head_on = lighting.shade(&normal, &Vector3::new(0.0, 0.0, -1.0), &material);
+    let mut side = Vector3::new(1.0, 0.0, -1.0);
+    side.normalize();
+    let glancing = lighting.shade(&normal, &side, &material);
+    assert_eq!(head_on, (177, 177, 177));
+    assert!(glancing.0 < head_on.0 && glancing.0 >= 50);
+}
+
+#[test]
+fn generate_normals_test () {
+    let mut hard = cube(2.0);
+    hard.generate_normals(30.0f32.to_radians());
+    for triangle in &hard.triangles() {
+        let face = calculate_normal(triangle);
+        for normal in triangle.normals.unwrap() {
+            assert!(dot_product(&normal, &face) > 0.9999);
+        }
+    }
+
+    let mut smooth = cube(2.0);
+    smooth.generate_normals(100.0f32.to_radians());
+    for triangle in &smooth.triangles() {
+        for (vertex, normal) in triangle.vertices.iter().zip(triangle.normals.unwrap()) {
+            // every corner normal points out of the cube through that corner
+            assert!(normal.x * vertex.x > 0.0 && normal.y * vertex.y > 0.0 && normal.z * vertex.z > 0.0);
+            assert!((calculate_magnitude(&normal) - 1.0).abs() < 0.0001);
+        }
+    }
+}
+
+#[test]
+fn parse_obj_normals_test () {
+    let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 0 2\nvt 0.5 0.25\nf 1/1/1 2/1/1 3/1/1\nf 1 2 3\n";
+    let groups = parse_obj(source.as_bytes()).unwrap();
+    let normals = groups[0].triangles[0].normals.unwrap();
+    assert_eq!(normals[2].z, 1.0);
+    assert_eq!(groups[0].triangles[0].uvs.unwrap()[1], Vector2::new(0.5, 0.25));
+    assert!(groups[0].triangles[1].normals.is_none());
+    assert!(groups[0].triangles[1].uvs.is_none());
+}
+
+#[test]
+fn texture_sample_test () {
+    // top row red and green, bottom row blue and white
+    let texture = Texture::new(2, 2, vec![255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255]).unwrap();
+    let red = texture.sample(0.25, 0.75, Filter::Nearest);
+    assert_eq!((red.x, red.y, red.z), (255.0, 0.0, 0.0));
+    let white = texture.sample(0.75, 0.25, Filter::Nearest);
+    assert_eq!((white.x, white.y, white.z), (255.0, 255.0, 255.0));
+    // coordinates wrap around
+    let wrapped = texture.sample(1.25, 1.75, Filter::Nearest);
+    assert_eq!((wrapped.x, wrapped.y, wrapped.z), (255.0, 0.0, 0.0));
+    // halfway between the red and green texel centers
+    let mixed = texture.sample(0.5, 0.75, Filter::Bilinear);
+    assert_eq!((mixed.x, mixed.y, mixed.z), (127.5, 127.5, 0.0));
+    let center = texture.sample(0.25, 0.75, Filter::Bilinear);
+    assert_eq!((center.x, center.y, center.z), (255.0, 0.0, 0.0));
+}
+
+#[test]
+fn texture_file_test () {
+    let mut framebuffer = Framebuffer::new(3, 2).unwrap();
+    framebuffer.set_pixel(0, 0, (10, 20, 30));
+    framebuffer.set_pixel(2, 1, (40, 50, 60));
+
+    let mut bmp = Vec::new();
+    write_bmp(&framebuffer, &mut bmp).unwrap();
+    let texture = parse_bmp(&bmp).unwrap();
+    assert_eq!((texture.width, texture.height), (3, 2));
+    assert_eq!(texture.data, framebuffer.color);
+
+    let mut ppm = Vec::new();
+    write_ppm(&framebuffer, &mut ppm).unwrap();
+    assert_eq!(parse_ppm(&ppm).unwrap().data, framebuffer.color);
+
+    let ascii = parse_ppm(b"P3\n# comment\n1 1\n15\n15 0 5\n").unwrap();
+    assert_eq!(ascii.data, vec![255, 0, 85]);
+}
+
+#[test]
+fn cube_uv_test () {
+    for triangle in &cube(3.0).triangles() {
+        for Vector2 { x: u, y: v } in triangle.uvs.unwrap() {
+            assert!(u == 0.0 || u == 1.0);
+            assert!(v == 0.0 || v == 1.0);
+        }
+    }
+}
+
+#[test]
+fn indexed_mesh_test () {
+    let cube1 = cube(2.0);
+    // corners with the same position and uv are stored once
+    assert!(cube1.geometry.vertices.len() < 36);
+    assert_eq!(cube1.geometry.triangle_count(), 12);
+
+    let triangles = cube1.triangles();
+    let round_trip = IndexedMesh::from_triangles(&triangles).to_triangles();
+    for (a, b) in triangles.iter().zip(&round_trip) {
+        for i in 0..3 {
+            assert_eq!(a.vertices[i].x, b.vertices[i].x);
+            assert_eq!(a.vertices[i].y, b.vertices[i].y);
+            assert_eq!(a.vertices[i].z, b.vertices[i].z);
+            assert_eq!(a.uvs.unwrap()[i], b.uvs.unwrap()[i]);
+        }
+        assert!(b.normals.is_none());
+    }
+
+    // the teapot repeats most of its positions
+    let teapot = teapot().unwrap();
+    assert!(teapot.geometry.vertices.len() * 4 < teapot.geometry.indices.len());
+
+    let vertices = vec![Vertex::new(Vector3::new(0.0, 0.0, 0.0)); 3];
+    assert_eq!(IndexedMesh::new(vertices.clone(), vec![0, 1, 2]).unwrap().triangle_count(), 1);
+    assert!(matches!(IndexedMesh::new(vertices.clone(), vec![0, 1]), Err(Error::Format(_))));
+    assert!(matches!(IndexedMesh::new(vertices, vec![0, 1, 3]), Err(Error::Format(_))));
+}
+
+#[test]
+fn scene_render_test () {
+    let camera = origin_camera();
+    let mut scene = Scene::new(camera, Lighting::unlit(), (0, 0, 0));
+    let mut near = cube(2.0);
+    near.translate(0.0, 0.0, 4.0);
+    let mut far = cube(2.0);
+    far.translate(0.0, 0.0, 8.0);
+    // the far cube is added last, the depth buffer should still hide it behind the near one
+    let index = scene.add(near, Material::new((255, 0, 0)));
+    scene.add(far, Material::new((0, 255, 0)));
+    assert_eq!(index, 0);
+
+    let mut framebuffer = Framebuffer::new(16, 16).unwrap();
+    render(&scene, &mut framebuffer);
+    assert_eq!(framebuffer.pixel(8, 8), Some((255, 0, 0)));
+    assert_eq!(framebuffer.pixel(0, 0), Some((0, 0, 0)));
+}
+
+#[test]
+fn scene_center_test () {
+    let mut scene = Scene::new(origin_camera(), Lighting::unlit(), (0, 0, 0));
+    assert!(scene.center().is_none());
+    scene.add(cube(1.0), Material::new((255, 0, 0)));
+    let mut moved = cube(1.0);
+    moved.translate(2.0, 4.0, 6.0);
+    scene.add(moved, Material::new((0, 255, 0)));
+    assert!(scene.center().unwrap().approx_eq(&Vector3::new(1.0, 2.0, 3.0), 0.0001));
+}
+
+#[test]
+fn parse_scene_test () {
+    let text = "
+background = [10, 20, 30]
+
+[camera]
+position = [0, 1, -5]
+h_fov = 90
+v_fov = 60 # comments are ignored
+z_near = 0.5
+z_far = 100
+
+[[light]]
+direction = [0, -1, 0]
+intensity = 0.5
+
+[[mesh]]
+builtin = \"cube\"
+position = [1, 2, 3]
+rotation = [0, 90, 0]
+color = [255, 0, 0]
+shading = \"gouraud\"
+outline = true
+";
+    let scene = parse_scene(text, Path::new("")).unwrap();
+    assert_eq!(scene.background, (10, 20, 30));
+    assert_eq!(scene.camera.position.y, 1.0);
+    assert_eq!(scene.camera.v_fov, 60.0);
+    assert_eq!(scene.lighting.directional.len(), 1);
+    assert_eq!(scene.objects.len(), 1);
+    let object = &scene.objects[0];
+    assert_eq!(object.mesh.position.z, 3.0);
+    assert!((object.mesh.rotation.to_euler(EulerOrder::Xyz).y - std::f32::consts::FRAC_PI_2).abs() < 0.0001);
+    assert_eq!(object.material.color, (255, 0, 0));
+    assert_eq!(object.material.shading, Shading::Gouraud);
+    assert!(object.material.outline);
+
+    let rotated = format!("{}[[mesh]]\nbuiltin = \"cube\"\nrotation = [90, 90, 0]\nrotation_order = \"yxz\"\n", CAMERA_TABLE);
+    let expected = Quaternion::from_euler(&Vector3::new(std::f32::consts::FRAC_PI_2, std::f32::consts::FRAC_PI_2, 0.0), EulerOrder::Yxz);
+    let v = Vector3::new(1.0, 2.0, 3.0);
+    assert!(parse_scene(&rotated, Path::new("")).unwrap().objects[0].mesh.rotation.rotate(&v).approx_eq(&expected.rotate(&v), 0.0001));
+
+    let target = format!("{}position = [0, 5, 0]\ntarget = [10, 5, 10]\n", CAMERA_TABLE);
+    let scene = parse_scene(&target, Path::new("")).unwrap();
+    assert!(scene.camera.forward().approx_eq(&Vector3::new(1.0, 0.0, 1.0).normalized(), 0.0001));
+
+    let orthographic = format!("{}projection = \"orthographic\"\nheight = 5\nreversed_z = true\n", CAMERA_TABLE);
+    let scene = parse_scene(&orthographic, Path::new("")).unwrap();
+    assert_eq!(scene.camera.projection, Projection::Orthographic { height: 5.0 });
+    assert!(scene.camera.reversed_z);
+
+    // the bundled example scene should always load
+    let example = std::fs::read_to_string("scenes/teapots.toml").unwrap();
+    assert_eq!(parse_scene(&example, Path::new("scenes")).unwrap().objects.len(), 3);
+}
+
+#[test]
+fn parse_scene_error_test () {
+    let error = |text: &str| parse_scene(text, Path::new("")).err().unwrap().to_string();
+    assert_eq!(error("[camera]\nh_fov = \"wide\"\n"), "line 2: field \"h_fov\": expected a number but found a string");
+    assert_eq!(error("[camera]\nh_fov = 90\nv_fov = 60\nz_near = 1\n"), "line 1: [camera] is missing field \"z_far\"");
+    assert_eq!(error("[camera]\nh_fov = 90\nv_fov = 60\nz_near = 1\nz_far = 9\nfov = 3\n"), "line 6: field \"fov\": unknown field in [camera]");
+    assert_eq!(error("[[mesh]]\nbuiltin = \"cube\"\ncolor = [300, 0, 0]\n"), "line 3: field \"color\": expected a list of three whole numbers from 0 to 255 but found a list");
+    assert_eq!(error("[lights]\n"), "line 1: unknown table [lights], expected [camera] or [ambient]");
+    assert_eq!(error(&format!("{}rotation = [0, 0, 0]\ntarget = [0, 0, 1]\n", CAMERA_TABLE)), "line 1: [camera] can't have both \"rotation\" and \"target\"");
+    assert_eq!(error(&format!("{}[[mesh]]\nbuiltin = \"cube\"\nrotation_order = \"abc\"\n", CAMERA_TABLE)), "line 8: field \"rotation_order\": expected one of \"xyz\", \"xzy\", \"yxz\", \"yzx\", \"zxy\" or \"zyx\"");
+    assert_eq!(error("z_far 9\n"), "line 1: expected \"key = value\" but found \"z_far 9\"");
+
+    // camera values that would leave nothing visible are caught with the line they are on
+    assert_eq!(error(&CAMERA_TABLE.replace("z_near = 1", "z_near = 10")), "line 4: field \"z_near\": (10) must be closer than z_far (9)");
+    assert_eq!(error(&CAMERA_TABLE.replace("z_near = 1", "z_near = 9")), "line 4: field \"z_near\": (9) must be closer than z_far (9)");
+    assert_eq!(error(&CAMERA_TABLE.replace("z_near = 1", "z_near = -1")), "line 4: field \"z_near\": must be greater than zero but got -1");
+    assert_eq!(error(&CAMERA_TABLE.replace("h_fov = 90", "h_fov = 0")), "line 2: field \"h_fov\": must be between 0 and 180 degrees but got 0");
+    assert_eq!(error(&CAMERA_TABLE.replace("v_fov = 90", "v_fov = 180")), "line 3: field \"v_fov\": must be between 0 and 180 degrees but got 180");
+    assert_eq!(error(&format!("{}projection = \"orthographic\"\nheight = 0\n", CAMERA_TABLE)), "line 7: field \"height\": must be greater than zero but got 0");
+    assert_eq!(error(&format!("{}projection = \"orthographic\"\n", CAMERA_TABLE)), "line 6: field \"projection\": an orthographic camera needs a \"height\" field");
+    assert_eq!(error(&format!("{}projection = \"fisheye\"\n", CAMERA_TABLE)), "line 6: field \"projection\": expected \"perspective\" or \"orthographic\"");
+}
+
+#[test]
+fn scene_graph_test () {
+    let camera = origin_camera();
+    let mut scene = Scene::new(camera, Lighting::unlit(), (0, 0, 0));
+    let mut body = cube(2.0);
+    body.translate(0.0, 0.0, 10.0);
+    body.rotate(0.0, std::f32::consts::FRAC_PI_2, 0.0);
+    body.non_uniform_scale(2.0, 2.0, 2.0);
+    let mut wheel = cube(1.0);
+    wheel.translate(1.0, 0.0, 0.0);
+    let mut bolt = cube(0.1);
+    bolt.translate(0.0, 1.0, 0.0);
+
+    let body = scene.add(body, Material::new((255, 0, 0)));
+    let wheel = scene.add_child(body, wheel, Material::new((0, 255, 0))).unwrap();
+    let bolt = scene.add_child(wheel, bolt, Material::new((0, 0, 255))).unwrap();
+    assert!(matches!(scene.add_child(bolt + 1, cube(1.0), Material::new((0, 0, 0))), Err(Error::Render(_))));
+    assert_eq!(scene.children(body).collect::<Vec<usize>>(), vec![wheel]);
+
+    // children inherit the whole chain of transforms above them
+    let transforms = scene.world_transforms();
+    let origin = Vector3::new(0.0, 0.0, 0.0);
+    let expected = transform_point(&transforms[body].model_matrix, &Vector3::new(1.0, 1.0, 0.0));
+    let actual = transform_point(&transforms[bolt].model_matrix, &origin);
+    assert!(calculate_magnitude(&subtract(&expected, &actual)) < 0.0001);
+    assert!((calculate_magnitude(&subtract(&actual, &Vector3::new(0.0, 0.0, 10.0))) - 8.0_f32.sqrt()).abs() < 0.0001);
+
+    // moving the parent moves the child with it
+    scene.objects[body].mesh.translate(5.0, 0.0, 0.0);
+    let moved = transform_point(&scene.world_transform(bolt).model_matrix, &origin);
+    assert!((moved.x - actual.x - 5.0).abs() < 0.0001);
+
+    // the parent's y rotation and uniform scale leave an up normal pointing up
+    let normal = transform_normal(&transforms[wheel].normal_matrix, &Vector3::new(0.0, 1.0, 0.0));
+    assert!((normal.y - 1.0).abs() < 0.0001);
+
+    let text = &format!("{}[[mesh]]\nbuiltin = \"cube\"\nname = \"body\"\n[[mesh]]\nbuiltin = \"cube\"\nparent = \"body\"\n[[mesh]]\nbuiltin = \"cube\"\nparent = \"wheel\"\n", CAMERA_TABLE);
+    assert_eq!(parse_scene(text, Path::new("")).err().unwrap().to_string(), "line 14: field \"parent\": no mesh named \"wheel\" above this one");
+    let scene = parse_scene(&text.replace("\"wheel\"", "\"body\""), Path::new("")).unwrap();
+    assert_eq!(scene.objects[2].parent, Some(0));
+}
+
+#[test]
+fn vertical_fov_test () {
+    assert!((vertical_fov(90.0, 1.0) - 90.0).abs() < 0.001);
+    // a wider image keeps the same horizontal fov and sees less vertically
+    assert!(vertical_fov(90.0, 2.0) < 90.0);
+    assert!((vertical_fov(90.0, 2.0) - 2.0 * 0.5_f32.atan().to_degrees()).abs() < 0.001);
+}
+
+#[test]
+fn parse_tris_test () {
+    let triangles = parse_tris("0 0 0\n1 0 0\n0 1 0\n\n0 0 1\n1 0 1\n0 1 1\n").unwrap();
+    assert_eq!(triangles.len(), 2);
+    assert_eq!(triangles[1].vertices[2].y, 1.0);
+    assert_eq!(parse_tris("0 0 0\n1 0\n").err().unwrap().to_string(), "line 2: expected \"x y z\" but found \"1 0\"");
+    assert_eq!(parse_tris("0 0 0\n1 0 x\n").err().unwrap().to_string(), "line 2: expected a number but found \"x\"");
+    assert_eq!(parse_tris("0 0 0\n1 0 0\n").err().unwrap().to_string(), "line 2: the file ends in the middle of a triangle");
+
+    // the bundled teapot is embedded in the binary
+    assert_eq!(teapot().unwrap().geometry.triangle_count(), 3488);
+}
+
+#[test]
+fn asset_paths_test () {
+    let directory = std::env::temp_dir().join(format!("renderer_assets_test_{}", std::process::id()));
+    std::fs::create_dir_all(directory.join("models")).unwrap();
+    std::fs::write(directory.join("models").join("triangle.tris"), "0 0 0\n1 0 0\n0 1 0\n").unwrap();
+
+    let assets = AssetPaths::new(vec![PathBuf::from("/nonexistent"), directory.clone()]);
+    let found = assets.find(Path::new("models/triangle.tris")).unwrap();
+    assert_eq!(found, directory.join("models").join("triangle.tris"));
+    assert_eq!(assets.load_model(Path::new("models/triangle.tris")).unwrap().geometry.triangle_count(), 1);
+
+    let error = assets.find(Path::new("missing.obj")).unwrap_err();
+    assert!(matches!(&error, Error::Io(error) if error.kind() == std::io::ErrorKind::NotFound));
+    assert!(error.to_string().contains("/nonexistent"));
+    assert!(load_model(&found.with_extension("stl")).is_err());
+
+    std::fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn error_test () {
+    // parse errors keep the line number apart from the message
+    match parse_obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 x\n".as_bytes()) {
+        Err(Error::Parse { line, message }) => {
+            assert_eq!(line, 4);
+            assert_eq!(message, "expected an index but found \"x\"");
+        },
+        other => panic!("expected a parse error, got {:?}", other.map(|groups| groups.len())),
+    }
+    assert!(matches!(parse_ppm(b"P6 2 2 65535\n"), Err(Error::Format(_))));
+    assert!(matches!(load_texture(Path::new("/nonexistent/texture.ppm")), Err(Error::Io(_))));
+
+    let camera = origin_camera();
+    let scene = Scene::new(camera, Lighting::unlit(), (0, 0, 0));
+    let error = render_to_file(&scene, 0, 10, Path::new("empty.png")).unwrap_err();
+    assert!(matches!(error, Error::Render(_)));
+    assert_eq!(error.to_string(), "render failed: can't render a 0x10 image");
+    let path = std::env::temp_dir().join(format!("renderer_error_test_{}.gif", std::process::id()));
+    assert!(matches!(save_image(&Framebuffer::new(1, 1).unwrap(), &path), Err(Error::Format(_))));
+    assert!(!path.exists());
+    assert!(matches!(Framebuffer::new(u32::MAX, u32::MAX), Err(Error::Render(_))));
+}
+
+#[test]
+fn texture_header_error_test () {
+    let is_format_error = |result: Result<Texture>| matches!(result, Err(Error::Format(_)));
+    assert!(is_format_error(Texture::new(2, 2, vec![0; 11])));
+    assert!(is_format_error(Texture::new(0, 0, Vec::new())));
+
+    // dimensions that overflow, dimensions with no pixels and pixel data cut short
+    assert!(is_format_error(parse_ppm(b"P6 4294967295 4294967295 255\n")));
+    assert!(is_format_error(parse_ppm(b"P6 0 0 255\n")));
+    assert!(is_format_error(parse_ppm(b"P3 0 1 255\n")));
+    assert!(is_format_error(parse_ppm(b"P6 2 2 255\n0123456789")));
+    assert!(is_format_error(parse_ppm(b"P3 100000 100000 255\n1 2 3\n")));
+    // values above the header's maximum are clamped to it like in ascii files
+    assert_eq!(parse_ppm(b"P6 1 1 15\n\xc8\x0f\x05").unwrap().data, vec![255, 255, 85]);
+
+    let mut bmp = Vec::new();
+    write_bmp(&Framebuffer::new(3, 2).unwrap(), &mut bmp).unwrap();
+    let with_size = |width: i32, height: i32| {
+        let mut bmp = bmp.clone();
+        bmp[18..22].copy_from_slice(&width.to_le_bytes());
+        bmp[22..26].copy_from_slice(&height.to_le_bytes());
+        bmp
+    };
+    assert!(is_format_error(parse_bmp(&with_size(i32::MAX, i32::MIN))));
+    assert!(is_format_error(parse_bmp(&with_size(0, 2))));
+    assert!(is_format_error(parse_bmp(&with_size(3, 0))));
+    assert!(is_format_error(parse_bmp(&bmp[..bmp.len() - 4])));
+    assert!(parse_bmp(&with_size(3, -2)).is_ok());
+}
+
+#[test]
+fn vector3_operators_test () {
+    let a = Vector3::new(1.0, 2.0, 3.0);
+    let b = Vector3::new(4.0, -5.0, 6.0);
+    assert!((a + b).approx_eq(&Vector3::new(5.0, -3.0, 9.0), 0.0));
+    assert!((a - b).approx_eq(&Vector3::new(-3.0, 7.0, -3.0), 0.0));
+    assert!((a * 2.0).approx_eq(&(2.0 * a), 0.0));
+    assert!((a * b).approx_eq(&Vector3::new(4.0, -10.0, 18.0), 0.0));
+    assert!((b / 2.0).approx_eq(&Vector3::new(2.0, -2.5, 3.0), 0.0));
+    assert!((-a).approx_eq(&Vector3::new(-1.0, -2.0, -3.0), 0.0));
+    assert_eq!((a[0], a[1], a[2]), (1.0, 2.0, 3.0));
+
+    let mut c = a;
+    c += b;
+    c -= a;
+    c *= 0.5;
+    c[1] = 0.0;
+    assert!(c.approx_eq(&Vector3::new(2.0, 0.0, 3.0), 0.0));
+
+    assert_eq!(a.dot(&b), dot_product(&a, &b));
+    assert!(a.cross(&b).approx_eq(&cross_product(&a, &b), 0.0));
+    assert_eq!(a.length_squared(), 14.0);
+    assert!((a.normalized().length() - 1.0).abs() < 0.0001);
+    assert!(Vector3::default().normalized().approx_eq(&Vector3::default(), 0.0));
+    assert!(a.lerp(&b, 0.5).approx_eq(&Vector3::new(2.5, -1.5, 4.5), 0.0));
+    assert!(a.min(&b).approx_eq(&Vector3::new(1.0, -5.0, 3.0), 0.0));
+    assert!(a.max(&b).approx_eq(&Vector3::new(4.0, 2.0, 6.0), 0.0));
+    assert!(!a.approx_eq(&Vector3::new(1.0, 2.0, 3.1), 0.01));
+
+    // a ray hitting the floor at 45 degrees bounces back up
+    let up = Vector3::new(0.0, 1.0, 0.0);
+    let ray = Vector3::new(1.0, -1.0, 0.0).normalized();
+    assert!(ray.reflect(&up).approx_eq(&Vector3::new(1.0, 1.0, 0.0).normalized(), 0.0001));
+
+    // refraction with matching indices goes straight through, and glass to air at a grazing angle reflects
+    assert!(ray.refract(&up, 1.0).unwrap().approx_eq(&ray, 0.0001));
+    let refracted = ray.refract(&up, 1.0 / 1.5).unwrap();
+    assert!((refracted.length() - 1.0).abs() < 0.0001);
+    assert!(refracted.x < ray.x);
+    assert!(Vector3::new(1.0, -0.1, 0.0).normalized().refract(&up, 1.5).is_none());
+}
+
+#[test]
+fn vector4_homogeneous_test () {
+    let translation = translation_matrix(1.0, 2.0, 3.0);
+    let point = translation * Vector4::from_point(&Vector3::new(1.0, 1.0, 1.0));
+    assert_eq!(point, Vector4::new(2.0, 3.0, 4.0, 1.0));
+    // directions ignore translation
+    let direction = translation * Vector4::from_direction(&Vector3::new(1.0, 1.0, 1.0));
+    assert_eq!(direction, Vector4::new(1.0, 1.0, 1.0, 0.0));
+    assert!(translation.transform_direction(&Vector3::new(0.0, 0.0, 1.0)).approx_eq(&Vector3::new(0.0, 0.0, 1.0), 0.0));
+
+    // matches the old [[f32; 1]; 4] multiply
+    let rotation = euler_rotation_matrix(&Vector3::new(0.3, -1.2, 2.0));
+    let v = Vector3::new(0.5, -2.0, 4.0);
+    let old = matrix_vector_multiply(&rotation, &v);
+    let new = rotation * Vector4::from_point(&v);
+    for i in 0..4 {
+        assert!((old[i][0] - new[i]).abs() < 0.0001);
+    }
+
+    let clip = Vector4::new(2.0, -4.0, 1.0, 4.0);
+    assert!(clip.perspective_divide().approx_eq(&Vector3::new(0.5, -1.0, 0.25), 0.0));
+    assert_eq!(Vector4::default().lerp(&clip, 0.5), Vector4::new(1.0, -2.0, 0.5, 2.0));
+    assert_eq!(clip.dot(&Vector4::new(1.0, 1.0, 1.0, 1.0)), 3.0);
+
+    let uv = Vector2::new(0.25, 1.0);
+    assert_eq!(uv.lerp(&Vector2::new(0.75, 0.0), 0.5), Vector2::new(0.5, 0.5));
+    assert_eq!((uv[0], uv[1]), (0.25, 1.0));
+}
+
+#[test]
+fn matrix_inverse_test () {
+    let m = Matrix4x4::from_translation_rotation_scale(&Vector3::new(3.0, -2.0, 5.0), &Quaternion::from_euler(&Vector3::new(0.3, -1.2, 2.0), EulerOrder::Xyz), &Vector3::new(2.0, 0.5, 4.0));
+    assert!((m.determinant() - 4.0).abs() < 0.0001);
+    assert_eq!(m.transpose().transpose(), m);
+
+    let product = m * m.inverse().unwrap();
+    for row in 0..4 {
+        for col in 0..4 {
+            let expected = if row == col { 1.0 } else { 0.0 };
+            assert!((product.m[row][col] - expected).abs() < 0.0001);
+        }
+    }
+    assert!(scale_matrix(1.0, 0.0, 1.0).inverse().is_none());
+
+    // normals stay perpendicular to a surface squashed by a non uniform scale
+    let mut mesh = cube(1.0);
+    mesh.rotate(0.0, 0.0, std::f32::consts::FRAC_PI_4);
+    mesh.non_uniform_scale(4.0, 1.0, 1.0);
+    let tangent = mesh.model_matrix().transform_direction(&Vector3::new(1.0, -1.0, 0.0));
+    let normal = transform_normal(&mesh.normal_matrix(), &Vector3::new(1.0, 1.0, 0.0));
+    assert!(tangent.dot(&normal).abs() < 0.0001);
+
+    // the view matrix undoes the camera's own placement
+    let camera = Camera::new(Vector3::new(1.0, 2.0, -3.0), Quaternion::from_euler(&Vector3::new(0.2, 0.4, -0.1), EulerOrder::Xyz), 90.0, 90.0, 0.1, 100.0);
+    let point = Vector3::new(4.0, -1.0, 7.0);
+    assert!(camera.world_matrix().transform_point(&camera.view_matrix().transform_point(&point)).approx_eq(&point, 0.0001));
+}
+
+#[test]
+fn pick_test () {
+    let camera = origin_camera();
+    let mut scene = Scene::new(camera, Lighting::unlit(), (0, 0, 0));
+    let mut near = cube(1.0);
+    near.translate(0.0, 0.0, 5.0);
+    let mut far = cube(4.0);
+    far.translate(0.0, 0.0, 20.0);
+    let mut side = cube(1.0);
+    side.translate(-5.0, 0.0, 5.0);
+    let far = scene.add(far, Material::new((255, 0, 0)));
+    let near = scene.add(near, Material::new((0, 255, 0)));
+    let side = scene.add(side, Material::new((0, 0, 255)));
+
+    let (origin, direction) = scene.camera.screen_ray(50.0, 50.0, 101, 101).unwrap();
+    assert!(origin.approx_eq(&Vector3::new(0.0, 0.0, 0.1), 0.0001));
+    assert!(direction.approx_eq(&Vector3::new(0.0, 0.0, 1.0), 0.0001));
+
+    // the nearest of the overlapping cubes wins, even though it was added later
+    assert_eq!(scene.pick(50.0, 50.0, 101, 101), Some(near));
+    assert_eq!(scene.pick(0.0, 50.0, 101, 101), Some(side));
+    assert_eq!(scene.pick(50.0, 0.0, 101, 101), None);
+    scene.objects[near].mesh.translate(0.0, 3.0, 0.0);
+    assert_eq!(scene.pick(50.0, 50.0, 101, 101), Some(far));
+}
+
+#[test]
+fn projection_matrix_test () {
+    let perspective = Matrix4x4::perspective(90.0, 2.0, 1.0, 10.0);
+    assert!(perspective.project_point(&Vector3::new(2.0, 1.0, 1.0)).approx_eq(&Vector3::new(1.0, 1.0, 0.0), 0.0001));
+    assert!(perspective.project_point(&Vector3::new(0.0, 0.0, 10.0)).approx_eq(&Vector3::new(0.0, 0.0, 1.0), 0.0001));
+    let symmetric = Matrix4x4::frustum(-2.0, 2.0, -1.0, 1.0, 1.0, 10.0);
+    assert!(symmetric.project_point(&Vector3::new(2.0, 1.0, 1.0)).approx_eq(&Vector3::new(1.0, 1.0, 0.0), 0.0001));
+
+    // an off axis frustum is lopsided, both its edges still land on the edges of the image
+    let off_axis = Matrix4x4::frustum(-1.0, 3.0, 0.0, 2.0, 1.0, 10.0);
+    assert!(off_axis.project_point(&Vector3::new(-5.0, 0.0, 5.0)).approx_eq(&Vector3::new(-1.0, -1.0, 0.8888889), 0.0001));
+    assert!(off_axis.project_point(&Vector3::new(15.0, 10.0, 5.0)).approx_eq(&Vector3::new(1.0, 1.0, 0.8888889), 0.0001));
+
+    let orthographic = Matrix4x4::orthographic(-4.0, 4.0, -2.0, 2.0, 1.0, 11.0);
+    assert!(orthographic.project_point(&Vector3::new(4.0, -2.0, 6.0)).approx_eq(&Vector3::new(1.0, -1.0, 0.5), 0.0001));
+    assert!(orthographic.project_point(&Vector3::new(4.0, -2.0, 60.0)).x == 1.0);
+
+    let reversed = perspective.reverse_depth();
+    assert!(reversed.project_point(&Vector3::new(0.0, 0.0, 1.0)).approx_eq(&Vector3::new(0.0, 0.0, 1.0), 0.0001));
+    assert!(reversed.project_point(&Vector3::new(0.0, 0.0, 10.0)).approx_eq(&Vector3::new(0.0, 0.0, 0.0), 0.0001));
+}
+
+#[test]
+fn orthographic_camera_test () {
+    // an orthographic camera looks the same way from everywhere, which is what culling and specular use
+    let mut camera = origin_camera();
+    camera.projection = Projection::Orthographic { height: 8.0 };
+    assert!(camera.view_ray(&Vector3::new(5.0, 3.0, 1.0)).approx_eq(&Vector3::new(0.0, 0.0, 1.0), 0.0001));
+    assert!(camera.to_eye(&Vector3::new(-5.0, 3.0, 1.0)).approx_eq(&Vector3::new(0.0, 0.0, -1.0), 0.0001));
+    assert_eq!(camera.orthographic_height(4.0), 8.0);
+}
+
+#[test]
+fn depth_compare_test () {
+    // the depth test and clear value follow the camera for every combination
+    for (projection, reversed_z) in [(Projection::Perspective, true), (Projection::Orthographic { height: 8.0 }, false), (Projection::Orthographic { height: 8.0 }, true)] {
+        let mut scene = Scene::new(origin_camera(), Lighting::unlit(), (0, 0, 0));
+        scene.camera.projection = projection;
+        scene.camera.reversed_z = reversed_z;
+        let mut near = cube(2.0);
+        near.translate(0.0, 0.0, 4.0);
+        let mut far = cube(2.0);
+        far.translate(0.0, 0.0, 8.0);
+        scene.add(near, Material::new((255, 0, 0)));
+        scene.add(far, Material::new((0, 255, 0)));
+
+        let mut framebuffer = Framebuffer::new(16, 16).unwrap();
+        render(&scene, &mut framebuffer);
+        assert_eq!(framebuffer.pixel(8, 8), Some((255, 0, 0)));
+        assert_eq!(framebuffer.pixel(0, 0), Some((0, 0, 0)));
+        let expected = if reversed_z { DepthCompare::Greater } else { DepthCompare::Less };
+        assert_eq!(framebuffer.depth_compare, expected);
+        assert_eq!(framebuffer.depth(0, 0), Some(expected.clear_value()));
+        // the picking ray follows the projection too
+        assert_eq!(scene.pick(8.0, 8.0, 16, 16), Some(0));
+    }
+}
+
+#[test]
+fn quaternion_test () {
+    let quarter_turn = Quaternion::from_axis_angle(&Vector3::new(0.0, 0.0, 2.0), std::f32::consts::FRAC_PI_2);
+    assert!(quarter_turn.rotate(&Vector3::new(1.0, 0.0, 0.0)).approx_eq(&Vector3::new(0.0, 1.0, 0.0), 0.0001));
+    let (axis, angle) = quarter_turn.to_axis_angle();
+    assert!(axis.approx_eq(&Vector3::new(0.0, 0.0, 1.0), 0.0001));
+    assert!((angle - std::f32::consts::FRAC_PI_2).abs() < 0.0001);
+
+    // the quaternion, its matrix and the old euler matrix all agree
+    let angles = Vector3::new(0.3, -1.2, 2.0);
+    let q = Quaternion::from_euler(&angles, EulerOrder::Xyz);
+    let v = Vector3::new(1.0, 2.0, 3.0);
+    assert!(q.rotate(&v).approx_eq(&euler_rotation_matrix(&angles).transform_direction(&v), 0.0001));
+    assert!(q.to_matrix().transform_direction(&v).approx_eq(&q.rotate(&v), 0.0001));
+    let back = Quaternion::from_matrix(&q.to_matrix());
+    assert!((back.dot(&q).abs() - 1.0).abs() < 0.0001);
+
+    // slerp moves at constant speed and takes the short way round
+    let half = Quaternion::identity().slerp(&quarter_turn, 0.5);
+    assert!((half.to_axis_angle().1 - std::f32::consts::FRAC_PI_4).abs() < 0.0001);
+    let flipped = Quaternion::new(-quarter_turn.x, -quarter_turn.y, -quarter_turn.z, -quarter_turn.w);
+    assert!((Quaternion::identity().slerp(&flipped, 0.5).dot(&half).abs() - 1.0).abs() < 0.0001);
+    assert!(Quaternion::identity().slerp(&quarter_turn, 1.0).rotate(&v).approx_eq(&quarter_turn.rotate(&v), 0.0001));
+}
+
+#[test]
+fn euler_order_test () {
+    let angles = Vector3::new(0.3, -1.2, 2.0);
+    let v = Vector3::new(1.0, 2.0, 3.0);
+    // every order gets its angles back, including a turned around matrix that takes the last branch of from_matrix
+    for order in EulerOrder::ALL {
+        for angles in [angles, Vector3::new(2.9, 0.1, -3.0)] {
+            let q = Quaternion::from_euler(&angles, order);
+            let round_trip = Quaternion::from_euler(&q.to_euler(order), order);
+            assert!((round_trip.dot(&q).abs() - 1.0).abs() < 0.0001, "{:?}", order);
+        }
+    }
+    // the order matters, x then y is not y then x
+    let xy = Quaternion::from_euler(&Vector3::new(0.5, 0.5, 0.0), EulerOrder::Xyz);
+    let yx = Quaternion::from_euler(&Vector3::new(0.5, 0.5, 0.0), EulerOrder::Yxz);
+    assert!(!xy.rotate(&v).approx_eq(&yx.rotate(&v), 0.01));
+    // gimbal lock still gives a rotation that matches
+    let locked = Quaternion::from_euler(&Vector3::new(0.4, std::f32::consts::FRAC_PI_2, 0.3), EulerOrder::Xyz);
+    assert!(Quaternion::from_euler(&locked.to_euler(EulerOrder::Xyz), EulerOrder::Xyz).rotate(&v).approx_eq(&locked.rotate(&v), 0.001));
+}
+
+#[test]
+fn camera_look_at_test () {
+    let target = Vector3::new(10.0, 5.0, 10.0);
+    let mut camera = Camera::looking_at(Vector3::new(0.0, 5.0, 0.0), &target, &Vector3::new(0.0, 1.0, 0.0), 90.0, 90.0, 0.1, 100.0);
+    assert!(camera.forward().approx_eq(&Vector3::new(1.0, 0.0, 1.0).normalized(), 0.0001));
+    assert!(camera.up().approx_eq(&Vector3::new(0.0, 1.0, 0.0), 0.0001));
+    assert!(camera.view_matrix().transform_point(&target).approx_eq(&Vector3::new(0.0, 0.0, 200.0_f32.sqrt()), 0.001));
+    // straight down has no horizon to keep level but still faces the right way
+    camera.turn_towards(&Vector3::new(0.0, -5.0, 0.0), &Vector3::new(0.0, 1.0, 0.0));
+    assert!(camera.forward().approx_eq(&Vector3::new(0.0, -1.0, 0.0), 0.0001));
+}
+
+#[test]
+fn orbit_controller_test () {
+    // starting an orbit leaves the camera where it is, facing the target
+    let mut camera = origin_camera();
+    let target = Vector3::new(0.0, 0.0, 10.0);
+    let mut orbit = OrbitController::from_camera(&camera, target);
+    orbit.apply(&mut camera);
+    assert!(camera.position.approx_eq(&Vector3::new(0.0, 0.0, 0.0), 0.0001));
+    assert!(camera.forward().approx_eq(&Vector3::new(0.0, 0.0, 1.0), 0.0001));
+
+    // dragging swings the camera around the target at the same distance and keeps it centered
+    orbit.drag(std::f32::consts::FRAC_PI_2 / 0.005, 0.0);
+    orbit.apply(&mut camera);
+    assert!(camera.position.approx_eq(&Vector3::new(-10.0, 0.0, 10.0), 0.001));
+    assert!(camera.forward().approx_eq(&Vector3::new(1.0, 0.0, 0.0), 0.0001));
+    // dragging far down stops short of looking straight down
+    orbit.drag(0.0, 10000.0);
+    orbit.apply(&mut camera);
+    assert!(camera.position.y > 9.9 && camera.forward().y > -1.0);
+    assert!(((camera.position - target).length() - 10.0).abs() < 0.001);
+
+    // the wheel zooms in and out again without ever passing the target
+    orbit.zoom(2.0);
+    assert!((orbit.distance - 8.1).abs() < 0.001);
+    orbit.zoom(-2.0);
+    assert!((orbit.distance - 10.0).abs() < 0.001);
+    orbit.zoom(1000.0);
+    assert!(orbit.distance > 0.0);
+    // zooming an orthographic view changes how much fits instead of moving closer
+    orbit.distance = 4.0;
+    camera.projection = Projection::Orthographic { height: 1.0 };
+    orbit.apply(&mut camera);
+    assert_eq!(camera.projection, Projection::Orthographic { height: camera.orthographic_height(4.0) });
+
+    // the viewer's first orbit keeps a scene's camera exactly as it was authored, tilt and all
+    let rotation = Quaternion::from_euler(&Vector3::new(0.3, -2.0, 0.2), EulerOrder::Xyz);
+    let mut camera = Camera::new(Vector3::new(1.0, 2.0, 3.0), rotation, 90.0, 90.0, 0.1, 100.0);
+    let orbit = OrbitController::from_view(&camera, 7.0);
+    orbit.apply(&mut camera);
+    assert!(camera.position.approx_eq(&Vector3::new(1.0, 2.0, 3.0), 0.0001));
+    assert!((camera.rotation.dot(&rotation).abs() - 1.0).abs() < 0.0001);
+    assert!(orbit.target.approx_eq(&(camera.position + camera.forward() * 7.0), 0.0001));
+}
+
+#[test]
+fn fly_controller_test () {
+    // mouse look keeps the horizon level and movement follows the view
+    let mut camera = origin_camera();
+    let mut fly = FlyController::from_camera(&camera, 2.0);
+    fly.look(std::f32::consts::FRAC_PI_2 / 0.005, std::f32::consts::FRAC_PI_4 / 0.005);
+    fly.apply(&mut camera);
+    assert!(camera.forward().approx_eq(&Vector3::new(1.0, -1.0, 0.0).normalized(), 0.0001));
+    assert!(camera.right().y.abs() < 0.0001);
+    fly.fly(&mut camera, 1.0, 0.0, 0.0);
+    assert!(camera.position.approx_eq(&(Vector3::new(1.0, -1.0, 0.0).normalized() * 2.0), 0.0001));
+    fly.fly(&mut camera, 0.0, 0.0, 1.0);
+    assert!((camera.position.y - (2.0 - 2.0_f32.sqrt())).abs() < 0.0001);
+    assert!((FlyController::from_camera(&camera, 1.0).pitch - std::f32::consts::FRAC_PI_4).abs() < 0.0001);
+}
+
+// how many times each pixel of a width by height target is visited by the triangles
+fn coverage (triangles: &[[ScreenPoint; 3]], width: u32, height: u32) -> Vec<u32> {
+    let mut counts = vec![0; (width * height) as usize];
+    for triangle in triangles {
+        rasterize_triangle(triangle, width, height, &mut |x, y, _| counts[(y as u32 * width + x as u32) as usize] += 1);
+    }
+    counts
+}
+
+#[test]
+fn rasterize_shared_edges_test () {
+    let point = |x: f32, y: f32| ScreenPoint::new(x, y, 0.5);
+
+    // a square split along its diagonal, the corners and the diagonal go straight through pixel centers
+    let (a, b, c, d) = (point(1.0, 1.0), point(6.0, 1.0), point(6.0, 6.0), point(1.0, 6.0));
+    let counts = coverage(&[[a, b, c], [a, c, d]], 8, 8);
+    for y in 0..8 {
+        for x in 0..8 {
+            // only the top and left sides of the square own the centers they pass through
+            let inside = (1..6).contains(&x) && (1..6).contains(&y);
+            assert_eq!(counts[y * 8 + x], inside as u32, "pixel {}, {}", x, y);
+        }
+    }
+    // the winding doesn't change which pixels are covered
+    assert_eq!(coverage(&[[a, c, b], [a, d, c]], 8, 8), counts);
+
+    // a grid of quads with uneven sub pixel corners, running past every side of the screen, covers each pixel once
+    let (width, height) = (23, 17);
+    let corner = |i: usize, j: usize| {
+        let jitter = |k: usize| ((k * 7919) % 97) as f32 / 97.0 - 0.5;
+        point(i as f32 * 4.0 - 3.0 + jitter(i * 31 + j), j as f32 * 4.0 - 3.0 + jitter(j * 17 + i + 5))
+    };
+    let mut triangles = Vec::new();
+    for j in 0..6 {
+        for i in 0..8 {
+            let (p00, p10, p01, p11) = (corner(i, j), corner(i + 1, j), corner(i, j + 1), corner(i + 1, j + 1));
+            // alternate the diagonal so edges run every which way
+            if (i + j) % 2 == 0 {
+                triangles.push([p00, p10, p11]);
+                triangles.push([p00, p11, p01]);
+            } else {
+                triangles.push([p00, p10, p01]);
+                triangles.push([p10, p11, p01]);
+            }
+        }
+    }
+    assert!(coverage(&triangles, width, height).iter().all(|&count| count == 1));
+
+    // a fan around a center that isn't on the pixel grid, every spoke is shared by two triangles
+    let center = point(10.3, 8.6);
+    let rim: Vec<ScreenPoint> = (0..13).map(|i| {
+        let angle = i as f32 / 13.0 * std::f32::consts::TAU;
+        point(center.x + 30.0 * angle.cos(), center.y + 30.0 * angle.sin())
+    }).collect();
+    let fan: Vec<[ScreenPoint; 3]> = (0..13).map(|i| [center, rim[i], rim[(i + 1) % 13]]).collect();
+    assert!(coverage(&fan, width, height).iter().all(|&count| count == 1));
+}
+
+#[test]
+fn rasterize_barycentric_test () {
+    let points = [ScreenPoint::new(0.25, 0.5, 0.5), ScreenPoint::new(12.75, 3.0, 0.5), ScreenPoint::new(4.5, 9.5, 0.5)];
+    let mut visited = 0;
+    rasterize_triangle(&points, 16, 16, &mut |x, y, weights| {
+        visited += 1;
+        assert!(weights.iter().all(|&weight| weight >= 0.0));
+        assert!((weights.iter().sum::<f32>() - 1.0).abs() < 0.0001);
+        // the weights rebuild the pixel center they were computed for
+        let px: f32 = (0..3).map(|i| weights[i] * points[i].x).sum();
+        let py: f32 = (0..3).map(|i| weights[i] * points[i].y).sum();
+        assert!((px - x as f32).abs() < 0.01 && (py - y as f32).abs() < 0.01);
+    });
+    assert!(visited > 40);
+
+    // triangles without area draw nothing and don't divide by zero
+    let degenerate = [
+        [ScreenPoint::new(1.0, 1.0, 0.5), ScreenPoint::new(5.0, 5.0, 0.5), ScreenPoint::new(3.0, 3.0, 0.5)],
+        [ScreenPoint::new(1.0, 4.0, 0.5), ScreenPoint::new(9.0, 4.0, 0.5), ScreenPoint::new(5.0, 4.0, 0.5)],
+        [ScreenPoint::new(2.0, 2.0, 0.5), ScreenPoint::new(2.0, 2.0, 0.5), ScreenPoint::new(2.0, 2.0, 0.5)],
+        [ScreenPoint::new(f32::NAN, 2.0, 0.5), ScreenPoint::new(8.0, 2.0, 0.5), ScreenPoint::new(2.0, 8.0, 0.5)],
+        [ScreenPoint::new(f32::INFINITY, 2.0, 0.5), ScreenPoint::new(8.0, 2.0, 0.5), ScreenPoint::new(2.0, 8.0, 0.5)],
+    ];
+    assert!(coverage(&degenerate, 16, 16).iter().all(|&count| count == 0));
+    // a sliver thinner than a pixel only gets the centers it covers, x = 9 to 14 but not 15 on its right edge
+    let sliver = [ScreenPoint::new(0.0, 3.1, 0.5), ScreenPoint::new(15.0, 3.1, 0.5), ScreenPoint::new(15.0, 2.93, 0.5)];
+    assert_eq!(coverage(&[sliver], 16, 16).iter().sum::<u32>(), 6);
+}
@@ -0,0 +1,191 @@
+use crate::error::{Error, Result};
+use crate::vector3::Vector3;
+
+use std::fs;
+use std::path::Path;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Filter {
+    Nearest,
+    Bilinear,
+}
+
+// an RGB24 image, texture coordinates wrap around and v = 0 is the bottom row like in OBJ files
+#[derive(Debug)]
+pub struct Texture {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl Texture {
+    pub fn new(width: u32, height: u32, data: Vec<u8>) -> Result<Self> {
+        if data.len() != pixel_bytes(width as usize, height as usize, 3)? {
+            return Err(Error::format("texture data must be width * height RGB24 pixels"));
+        }
+        Ok(Self { width, height, data })
+    }
+
+    pub fn checkerboard(size: u32, squares: u32, color1: (u8, u8, u8), color2: (u8, u8, u8)) -> Self {
+        let size = size.max(1);
+        let square = (size / squares.max(1)).max(1);
+        let mut data = Vec::with_capacity(size as usize * size as usize * 3);
+        for y in 0..size {
+            for x in 0..size {
+                let color = if (x / square + y / square).is_multiple_of(2) { color1 } else { color2 };
+                data.extend_from_slice(&[color.0, color.1, color.2]);
+            }
+        }
+        Self { width: size, height: size, data }
+    }
+
+    pub fn texel(&self, x: i64, y: i64) -> Vector3 {
+        let x = x.rem_euclid(self.width as i64) as usize;
+        let y = y.rem_euclid(self.height as i64) as usize;
+        let i = (y * self.width as usize + x) * 3;
+        Vector3::new(self.data[i] as f32, self.data[i + 1] as f32, self.data[i + 2] as f32)
+    }
+
+    // returns the color at (u, v) with channels from 0 to 255
+    pub fn sample(&self, u: f32, v: f32, filter: Filter) -> Vector3 {
+        let x = u * self.width as f32;
+        let y = (1.0 - v) * self.height as f32;
+        match filter {
+            Filter::Nearest => self.texel(x.floor() as i64, y.floor() as i64),
+            Filter::Bilinear => {
+                // texel centers sit at half integer coordinates
+                let x = x - 0.5;
+                let y = y - 0.5;
+                let x0 = x.floor();
+                let y0 = y.floor();
+                let tx = x - x0;
+                let ty = y - y0;
+                let (x0, y0) = (x0 as i64, y0 as i64);
+                let top = self.texel(x0, y0).lerp(&self.texel(x0 + 1, y0), tx);
+                let bottom = self.texel(x0, y0 + 1).lerp(&self.texel(x0 + 1, y0 + 1), tx);
+                top.lerp(&bottom, ty)
+            },
+        }
+    }
+}
+
+// the number of bytes in a width by height image, an empty image would leave nothing to sample
+fn pixel_bytes(width: usize, height: usize, bytes_per_pixel: usize) -> Result<usize> {
+    if width == 0 || height == 0 {
+        return Err(Error::format("image has no pixels"));
+    }
+    width.checked_mul(height)
+        .and_then(|pixels| pixels.checked_mul(bytes_per_pixel))
+        .ok_or_else(|| Error::format(format!("image size {}x{} is too large", width, height)))
+}
+
+// reads a binary or ascii PPM, or an uncompressed 24/32 bit BMP
+pub fn load_texture(path: &Path) -> Result<Texture> {
+    let bytes = fs::read(path)?;
+    if bytes.starts_with(b"P6") || bytes.starts_with(b"P3") {
+        parse_ppm(&bytes)
+    } else if bytes.starts_with(b"BM") {
+        parse_bmp(&bytes)
+    } else {
+        Err(Error::format(format!("{} is not a PPM or BMP image", path.display())))
+    }
+}
+
+// http://netpbm.sourceforge.net/doc/ppm.html
+pub fn parse_ppm(bytes: &[u8]) -> Result<Texture> {
+    let mut cursor = 0;
+    let mut header = [0u32; 3];
+    let magic = next_ppm_token(bytes, &mut cursor).ok_or_else(|| Error::format("PPM header is missing"))?;
+    for value in &mut header {
+        let token = next_ppm_token(bytes, &mut cursor).ok_or_else(|| Error::format("PPM header is truncated"))?;
+        *value = token.parse().map_err(|_| Error::format(format!("bad PPM header value \"{}\"", token)))?;
+    }
+    let [width, height, max] = header;
+    if max == 0 || max > 255 {
+        return Err(Error::format("only 8 bit PPM images are supported"));
+    }
+    let count = pixel_bytes(width as usize, height as usize, 3)?;
+    let scale = |value: u32| (value * 255 / max) as u8;
+
+    let data = match magic.as_str() {
+        "P6" => {
+            // a single whitespace byte separates the header from the pixels
+            let start = cursor + 1;
+            let pixels = start.checked_add(count).and_then(|end| bytes.get(start..end)).ok_or_else(|| Error::format("PPM pixel data is truncated"))?;
+            pixels.iter().map(|value| scale((*value as u32).min(max))).collect()
+        },
+        _ => {
+            // every value takes at least two bytes, so a short file is caught before reserving for a huge header
+            if count > bytes.len().saturating_sub(cursor) / 2 {
+                return Err(Error::format("PPM pixel data is truncated"));
+            }
+            let mut data = Vec::with_capacity(count);
+            for _ in 0..count {
+                let token = next_ppm_token(bytes, &mut cursor).ok_or_else(|| Error::format("PPM pixel data is truncated"))?;
+                let value: u32 = token.parse().map_err(|_| Error::format(format!("bad PPM pixel value \"{}\"", token)))?;
+                data.push(scale(value.min(max)));
+            }
+            data
+        },
+    };
+    Texture::new(width, height, data)
+}
+
+fn next_ppm_token(bytes: &[u8], cursor: &mut usize) -> Option<String> {
+    loop {
+        match bytes.get(*cursor)? {
+            b'#' => while *bytes.get(*cursor)? != b'\n' { *cursor += 1 },
+            byte if byte.is_ascii_whitespace() => *cursor += 1,
+            _ => break,
+        }
+    }
+    let start = *cursor;
+    while bytes.get(*cursor).is_some_and(|byte| !byte.is_ascii_whitespace()) {
+        *cursor += 1;
+    }
+    Some(String::from_utf8_lossy(&bytes[start..*cursor]).into_owned())
+}
+
+// https://en.wikipedia.org/wiki/BMP_file_format
+pub fn parse_bmp(bytes: &[u8]) -> Result<Texture> {
+    let read_u16 = |offset: usize| bytes.get(offset..offset + 2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+    let read_u32 = |offset: usize| bytes.get(offset..offset + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+    let truncated = || Error::format("BMP header is truncated");
+
+    let data_offset = read_u32(10).ok_or_else(truncated)? as usize;
+    let width = read_u32(18).ok_or_else(truncated)? as i32;
+    let height = read_u32(22).ok_or_else(truncated)? as i32;
+    let bits = read_u16(28).ok_or_else(truncated)?;
+    let compression = read_u32(30).ok_or_else(truncated)?;
+    // 32 bit images may use BI_BITFIELDS, we assume the common BGRA layout
+    if !(bits == 24 && compression == 0 || bits == 32 && (compression == 0 || compression == 3)) {
+        return Err(Error::format("only uncompressed 24 and 32 bit BMP images are supported"));
+    }
+    if width <= 0 || height == 0 {
+        return Err(Error::format("BMP image has no pixels"));
+    }
+
+    // a negative height means the rows are stored top down
+    let top_down = height < 0;
+    let (width, height) = (width as usize, height.unsigned_abs() as usize);
+    let bytes_per_pixel = bits as usize / 8;
+    let too_large = || Error::format(format!("BMP image size {}x{} is too large", width, height));
+    let row_bytes = width.checked_mul(bytes_per_pixel).ok_or_else(too_large)?;
+    let row_size = row_bytes.checked_add(3).ok_or_else(too_large)? & !3;
+    // the pixels have to be in the file before anything is allocated for them
+    let end = row_size.checked_mul(height).and_then(|size| size.checked_add(data_offset)).ok_or_else(too_large)?;
+    if end - row_size + row_bytes > bytes.len() {
+        return Err(Error::format("BMP pixel data is truncated"));
+    }
+
+    let mut data = Vec::with_capacity(pixel_bytes(width, height, 3)?);
+    for y in 0..height {
+        let row = if top_down { y } else { height - 1 - y };
+        let start = data_offset + row * row_size;
+        let pixels = &bytes[start..start + row_bytes];
+        for bgr in pixels.chunks_exact(bytes_per_pixel) {
+            data.extend_from_slice(&[bgr[2], bgr[1], bgr[0]]);
+        }
+    }
+    Texture::new(width as u32, height as u32, data)
+}
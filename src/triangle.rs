@@ -1,20 +1,64 @@
-use crate::vector3::{Vector3, subtract, cross_product};
+use crate::vector2::Vector2;
+use crate::vector3::Vector3;
 
 #[derive(Copy, Clone, Debug)]
 pub struct Triangle {
     pub vertices: [Vector3; 3],
+    // per vertex normals for smooth shading, the face normal is used when there are none
+    pub normals: Option<[Vector3; 3]>,
+    // texture coordinates
+    pub uvs: Option<[Vector2; 3]>,
 }
 
 impl Triangle {
     pub fn new(vertices: [Vector3; 3]) -> Self {
-        Self { vertices }
+        Self { vertices, normals: None, uvs: None }
+    }
+
+    pub fn with_normals(vertices: [Vector3; 3], normals: [Vector3; 3]) -> Self {
+        Self { vertices, normals: Some(normals), uvs: None }
+    }
+
+    pub fn with_uvs(vertices: [Vector3; 3], uvs: [Vector2; 3]) -> Self {
+        Self { vertices, normals: None, uvs: Some(uvs) }
+    }
+
+    pub fn vertex_normals(&self) -> [Vector3; 3] {
+        self.normals.unwrap_or([calculate_normal(self); 3])
     }
 }
 
 pub fn calculate_normal(triangle: &Triangle) -> Vector3 {
-    let line1 = subtract(&triangle.vertices[1], &triangle.vertices[0]);
-    let line2 = subtract(&triangle.vertices[2], &triangle.vertices[0]);
-    let mut normal = cross_product(&line1, &line2);
-    normal.normalize();
-    normal
-}
\ No newline at end of file
+    calculate_area_normal(triangle).normalized()
+}
+
+// cross product of two edges, its length is twice the triangle's area
+pub fn calculate_area_normal(triangle: &Triangle) -> Vector3 {
+    let line1 = triangle.vertices[1] - triangle.vertices[0];
+    let line2 = triangle.vertices[2] - triangle.vertices[0];
+    line1.cross(&line2)
+}
+
+// distance along the ray to where it hits the triangle, from either side, none when it misses
+pub fn intersect_ray(triangle: &Triangle, origin: &Vector3, direction: &Vector3) -> Option<f32> {
+    let edge1 = triangle.vertices[1] - triangle.vertices[0];
+    let edge2 = triangle.vertices[2] - triangle.vertices[0];
+    let p = direction.cross(&edge2);
+    let determinant = edge1.dot(&p);
+    // the ray runs parallel to the triangle, or the triangle has no area
+    if determinant.abs() < f32::EPSILON {
+        return None;
+    }
+    let to_origin = *origin - triangle.vertices[0];
+    let u = to_origin.dot(&p) / determinant;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = to_origin.cross(&edge1);
+    let v = direction.dot(&q) / determinant;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let distance = edge2.dot(&q) / determinant;
+    (distance >= 0.0).then_some(distance)
+}
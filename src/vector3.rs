@@ -1,4 +1,6 @@
-#[derive(Copy, Clone, Debug)]
+use std::ops::{Add, AddAssign, Div, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign};
+
+#[derive(Copy, Clone, Debug, Default)]
 pub struct Vector3 {
     pub x: f32,
     pub y: f32,
@@ -42,36 +44,189 @@ impl Vector3 {
     pub fn negate(&mut self) {
         self.scale(-1.0)
     }
+
+    pub fn dot(&self, other: &Vector3) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(&self, other: &Vector3) -> Vector3 {
+        Vector3::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    pub fn length(&self) -> f32 {
+        self.length_squared().sqrt()
+    }
+
+    // cheaper than length when only comparing distances
+    pub fn length_squared(&self) -> f32 {
+        self.dot(self)
+    }
+
+    // a unit length copy, the zero vector stays zero
+    pub fn normalized(&self) -> Vector3 {
+        let mut v = *self;
+        v.normalize();
+        v
+    }
+
+    pub fn lerp(&self, other: &Vector3, t: f32) -> Vector3 {
+        *self + (*other - *self) * t
+    }
+
+    // mirrors the vector around a unit normal, e.g. a light ray bouncing off a surface
+    pub fn reflect(&self, normal: &Vector3) -> Vector3 {
+        *self - *normal * (2.0 * self.dot(normal))
+    }
+
+    // bends a unit vector passing through a surface with unit normal, eta is the ratio of the
+    // refractive indices on either side, None means the ray is totally internally reflected
+    pub fn refract(&self, normal: &Vector3, eta: f32) -> Option<Vector3> {
+        let cos_i = -self.dot(normal);
+        let k = 1.0 - eta * eta * (1.0 - cos_i * cos_i);
+        if k < 0.0 {
+            return None;
+        }
+        Some(*self * eta + *normal * (eta * cos_i - k.sqrt()))
+    }
+
+    pub fn min(&self, other: &Vector3) -> Vector3 {
+        Vector3::new(self.x.min(other.x), self.y.min(other.y), self.z.min(other.z))
+    }
+
+    pub fn max(&self, other: &Vector3) -> Vector3 {
+        Vector3::new(self.x.max(other.x), self.y.max(other.y), self.z.max(other.z))
+    }
+
+    // every component is within epsilon of the other vector's
+    pub fn approx_eq(&self, other: &Vector3, epsilon: f32) -> bool {
+        (self.x - other.x).abs() <= epsilon && (self.y - other.y).abs() <= epsilon && (self.z - other.z).abs() <= epsilon
+    }
+}
+
+impl Add for Vector3 {
+    type Output = Vector3;
+
+    fn add(self, other: Vector3) -> Vector3 {
+        Vector3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Vector3 {
+    type Output = Vector3;
+
+    fn sub(self, other: Vector3) -> Vector3 {
+        Vector3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Mul<f32> for Vector3 {
+    type Output = Vector3;
+
+    fn mul(self, k: f32) -> Vector3 {
+        Vector3::new(self.x * k, self.y * k, self.z * k)
+    }
+}
+
+impl Mul<Vector3> for f32 {
+    type Output = Vector3;
+
+    fn mul(self, v: Vector3) -> Vector3 {
+        v * self
+    }
+}
+
+// component-wise, mostly for modulating colors
+impl Mul for Vector3 {
+    type Output = Vector3;
+
+    fn mul(self, other: Vector3) -> Vector3 {
+        Vector3::new(self.x * other.x, self.y * other.y, self.z * other.z)
+    }
+}
+
+impl Div<f32> for Vector3 {
+    type Output = Vector3;
+
+    fn div(self, k: f32) -> Vector3 {
+        Vector3::new(self.x / k, self.y / k, self.z / k)
+    }
+}
+
+impl Neg for Vector3 {
+    type Output = Vector3;
+
+    fn neg(self) -> Vector3 {
+        Vector3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl AddAssign for Vector3 {
+    fn add_assign(&mut self, other: Vector3) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Vector3 {
+    fn sub_assign(&mut self, other: Vector3) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign<f32> for Vector3 {
+    fn mul_assign(&mut self, k: f32) {
+        *self = *self * k;
+    }
+}
+
+// x, y and z are components 0, 1 and 2
+impl Index<usize> for Vector3 {
+    type Output = f32;
+
+    fn index(&self, i: usize) -> &f32 {
+        match i {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("Vector3 index {} is out of range", i),
+        }
+    }
+}
+
+impl IndexMut<usize> for Vector3 {
+    fn index_mut(&mut self, i: usize) -> &mut f32 {
+        match i {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            2 => &mut self.z,
+            _ => panic!("Vector3 index {} is out of range", i),
+        }
+    }
 }
 
 pub fn calculate_magnitude(v: &Vector3) -> f32 {
-    (v.x.powf(2.0) + v.y.powf(2.0) + v.z.powf(2.0)).sqrt()
+    v.length()
 }
 
 pub fn add(v1: &Vector3, v2: &Vector3) -> Vector3 {
-    Vector3 {
-        x: v1.x + v2.x,
-        y: v1.y + v2.y,
-        z: v1.z + v2.z,
-    }
+    *v1 + *v2
 }
 
 pub fn subtract(v1: &Vector3, v2: &Vector3) -> Vector3 {
-    Vector3 {
-        x: v1.x - v2.x,
-        y: v1.y - v2.y,
-        z: v1.z - v2.z,
-    }
+    *v1 - *v2
 }
 
 pub fn dot_product(v1: &Vector3, v2: &Vector3) -> f32 {
-    v1.x * v2.x + v1.y * v2.y + v1.z * v2.z
+    v1.dot(v2)
 }
 
 pub fn cross_product(v1: &Vector3, v2: &Vector3) -> Vector3 {
-    Vector3 {
-        x: v1.y * v2.z - v1.z * v2.y,
-        y: v1.z * v2.x - v1.x * v2.z,
-        z: v1.x * v2.y - v1.y * v2.x,
-    }
-}
\ No newline at end of file
+    v1.cross(v2)
+}
+
+pub fn lerp(v1: &Vector3, v2: &Vector3, t: f32) -> Vector3 {
+    v1.lerp(v2, t)
+}
@@ -0,0 +1,82 @@
+use crate::vector3::Vector3;
+
+use std::ops::{Add, Index, Mul, Sub};
+
+// homogeneous coordinates, points have w = 1 and directions w = 0 until a projection changes w
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Vector4 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Vector4 {
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    pub fn from_point(point: &Vector3) -> Self {
+        Self::new(point.x, point.y, point.z, 1.0)
+    }
+
+    pub fn from_direction(direction: &Vector3) -> Self {
+        Self::new(direction.x, direction.y, direction.z, 0.0)
+    }
+
+    // drops w without dividing, for affine results
+    pub fn xyz(&self) -> Vector3 {
+        Vector3::new(self.x, self.y, self.z)
+    }
+
+    // the homogeneous divide, clip space to normalized device coordinates
+    pub fn perspective_divide(&self) -> Vector3 {
+        self.xyz() * (1.0 / self.w)
+    }
+
+    pub fn dot(&self, other: &Vector4) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    pub fn lerp(&self, other: &Vector4, t: f32) -> Vector4 {
+        *self + (*other - *self) * t
+    }
+}
+
+impl Add for Vector4 {
+    type Output = Vector4;
+
+    fn add(self, other: Vector4) -> Vector4 {
+        Vector4::new(self.x + other.x, self.y + other.y, self.z + other.z, self.w + other.w)
+    }
+}
+
+impl Sub for Vector4 {
+    type Output = Vector4;
+
+    fn sub(self, other: Vector4) -> Vector4 {
+        Vector4::new(self.x - other.x, self.y - other.y, self.z - other.z, self.w - other.w)
+    }
+}
+
+impl Mul<f32> for Vector4 {
+    type Output = Vector4;
+
+    fn mul(self, k: f32) -> Vector4 {
+        Vector4::new(self.x * k, self.y * k, self.z * k, self.w * k)
+    }
+}
+
+impl Index<usize> for Vector4 {
+    type Output = f32;
+
+    fn index(&self, i: usize) -> &f32 {
+        match i {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            3 => &self.w,
+            _ => panic!("Vector4 index {} is out of range", i),
+        }
+    }
+}
@@ -0,0 +1,181 @@
+// an interactive SDL2 window around the renderer, only built with the sdl feature
+use nicks_software_renderer::camera::{Camera, Projection};
+use nicks_software_renderer::controller::{FlyController, OrbitController};
+use nicks_software_renderer::error::{Error, Result};
+use nicks_software_renderer::framebuffer::Framebuffer;
+use nicks_software_renderer::material::Shading;
+use nicks_software_renderer::scene::{Scene, render};
+
+use sdl2::EventPump;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::keyboard::Scancode;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::{Canvas, Texture, RenderTarget};
+
+use std::time::Duration;
+
+// world units the fly camera moves per frame with a key held
+const MOVE_SPEED: f32 = 0.1;
+// the arrow keys turn the camera as if the mouse had been dragged this many pixels per frame
+const KEY_TURN: f32 = 5.0;
+// where the orbit target goes when the scene is empty
+const FOCUS_DISTANCE: f32 = 10.0;
+
+// F switches between the two, orbiting is the default since it suits looking at a model
+enum Controller {
+    Orbit(OrbitController),
+    Fly(FlyController),
+}
+
+impl Controller {
+    fn drag(&mut self, dx: f32, dy: f32) {
+        match self {
+            Controller::Orbit(orbit) => orbit.drag(dx, dy),
+            Controller::Fly(fly) => fly.look(dx, dy),
+        }
+    }
+
+    fn scroll(&mut self, steps: f32) {
+        match self {
+            Controller::Orbit(orbit) => orbit.zoom(steps),
+            Controller::Fly(fly) => fly.speed *= 1.25_f32.powf(steps),
+        }
+    }
+
+    // how far away the thing being looked at is, orthographic views are sized to match it
+    fn focus_distance(&self) -> f32 {
+        match self {
+            Controller::Orbit(orbit) => orbit.distance,
+            Controller::Fly(_) => FOCUS_DISTANCE,
+        }
+    }
+}
+
+// -1, 0 or 1 from a pair of opposing keys
+fn axis (event_pump: &EventPump, negative: Scancode, positive: Scancode) -> f32 {
+    let keyboard = event_pump.keyboard_state();
+    keyboard.is_scancode_pressed(positive) as i32 as f32 - keyboard.is_scancode_pressed(negative) as i32 as f32
+}
+
+fn handle_input (event_pump: &EventPump, controller: &mut Controller, camera: &mut Camera) {
+    controller.drag(axis(event_pump, Scancode::Left, Scancode::Right) * KEY_TURN, axis(event_pump, Scancode::Up, Scancode::Down) * KEY_TURN);
+    let forward = axis(event_pump, Scancode::S, Scancode::W);
+    match controller {
+        Controller::Orbit(orbit) => {
+            orbit.zoom(forward * 0.25);
+            orbit.apply(camera);
+        },
+        Controller::Fly(fly) => {
+            fly.apply(camera);
+            let right = axis(event_pump, Scancode::A, Scancode::D);
+            let up = axis(event_pump, Scancode::LCtrl, Scancode::Space);
+            fly.fly(camera, forward, right, up);
+        },
+    }
+}
+
+fn control_fov (event_pump: &EventPump, camera: &mut Camera) {
+    if event_pump.keyboard_state().is_scancode_pressed(Scancode::O) { 
+        camera.h_fov -= 0.25;
+    }
+    if event_pump.keyboard_state().is_scancode_pressed(Scancode::P) { 
+        camera.h_fov += 0.25;
+    }
+    if event_pump.keyboard_state().is_scancode_pressed(Scancode::K) { 
+        camera.v_fov -= 0.25;
+    }
+    if event_pump.keyboard_state().is_scancode_pressed(Scancode::L) { 
+        camera.v_fov += 0.25;
+    }
+}
+
+fn present<T: RenderTarget> (canvas: &mut Canvas<T>, texture: &mut Texture, framebuffer: &Framebuffer) -> Result<()> {
+    texture.update(None, &framebuffer.color, framebuffer.pitch()).map_err(Error::render)?;
+    canvas.copy(texture, None, None).map_err(Error::render)?;
+    canvas.present();
+    Ok(())
+}
+
+// switches between perspective and orthographic, sized so things at distance keep their size on screen
+fn toggle_projection (camera: &mut Camera, distance: f32) {
+    camera.projection = match camera.projection {
+        Projection::Perspective => Projection::Orthographic { height: camera.orthographic_height(distance) },
+        Projection::Orthographic { .. } => Projection::Perspective,
+    };
+}
+
+// opens a window and draws the scene every frame until it is closed or the frame count is reached
+pub fn run_window(mut scene: Scene, width: u32, height: u32, frames: Option<u32>) -> Result<()> {
+    let sdl_context = sdl2::init().map_err(Error::render)?;
+    let video_subsystem = sdl_context.video().map_err(Error::render)?;
+    let window = video_subsystem.window("rust-sdl2 cube playground", width, height)
+        .position_centered()
+        .build()
+        .map_err(Error::render)?;
+    let mut canvas = window.into_canvas().present_vsync().build().map_err(Error::render)?;
+    let mut event_pump = sdl_context.event_pump().map_err(Error::render)?;
+    let texture_creator = canvas.texture_creator();
+    let mut texture = texture_creator
+        .create_texture_streaming(PixelFormatEnum::RGB24, width, height)
+        .map_err(Error::render)?;
+    let mut framebuffer = Framebuffer::new(width, height)?;
+    let mut frame = 0;
+
+    let target = scene.center().unwrap_or(scene.camera.position + scene.camera.forward() * FOCUS_DISTANCE);
+    // orbits at the scene's distance but straight ahead, so the first frame matches what a headless render shows
+    let mut controller = Controller::Orbit(OrbitController::from_view(&scene.camera, (target - scene.camera.position).length()));
+
+    'running: loop {
+        for event in event_pump.poll_iter() {
+            match event {
+                Event::Quit { .. } |
+                Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
+                    break 'running
+                },
+                Event::KeyDown { keycode: Some(Keycode::Tab), .. } => {
+                    for material in scene.materials_mut() {
+                        material.outline = !material.outline;
+                    }
+                },
+                Event::KeyDown { keycode: Some(keycode @ (Keycode::Num1 | Keycode::Num2 | Keycode::Num3)), .. } => {
+                    let shading = match keycode {
+                        Keycode::Num1 => Shading::Flat,
+                        Keycode::Num2 => Shading::Gouraud,
+                        _ => Shading::Phong,
+                    };
+                    for material in scene.materials_mut() {
+                        material.shading = shading;
+                    }
+                },
+                Event::KeyDown { keycode: Some(Keycode::V), .. } => toggle_projection(&mut scene.camera, controller.focus_distance()),
+                Event::KeyDown { keycode: Some(Keycode::F), .. } => {
+                    controller = match controller {
+                        Controller::Orbit(_) => Controller::Fly(FlyController::from_camera(&scene.camera, MOVE_SPEED)),
+                        // turns back towards the scene from wherever the fly camera ended up
+                        Controller::Fly(_) => Controller::Orbit(OrbitController::from_camera(&scene.camera, target)),
+                    };
+                },
+                Event::MouseMotion { mousestate, xrel, yrel, .. } if mousestate.left() => {
+                    controller.drag(xrel as f32, yrel as f32);
+                },
+                Event::MouseWheel { y, .. } => controller.scroll(y as f32),
+                _ => {}
+            }
+        }
+
+        handle_input(&event_pump, &mut controller, &mut scene.camera);
+        control_fov(&event_pump, &mut scene.camera);
+
+        render(&scene, &mut framebuffer);
+
+        present(&mut canvas, &mut texture, &framebuffer)?;
+
+        frame += 1;
+        if frames.is_some_and(|frames| frame >= frames) {
+            break 'running;
+        }
+        ::std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 60));
+    }
+    Ok(())
+}